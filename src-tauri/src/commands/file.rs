@@ -1,4 +1,5 @@
 use crate::models::file::FileEntry;
+use crate::services::ignore_rules::IgnoreRules;
 use crate::utils::path::validate_path;
 use std::fs;
 use std::path::Path;
@@ -18,13 +19,20 @@ pub fn get_home_directory() -> Result<String, String> {
 }
 
 #[command]
-pub fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
+pub fn read_directory(path: String, hide_ignored: Option<bool>) -> Result<Vec<FileEntry>, String> {
     let validated_path = validate_path(&path)?;
     
     if !validated_path.is_dir() {
         return Err("Path is not a directory".to_string());
     }
     
+    // Only load ignore files when the caller asked for filtering
+    let ignore_rules = if hide_ignored.unwrap_or(false) {
+        Some(IgnoreRules::for_directory(&validated_path))
+    } else {
+        None
+    };
+    
     let entries = fs::read_dir(&validated_path)
         .map_err(|e| format!("Failed to read directory: {}", e))?;
     
@@ -46,6 +54,12 @@ pub fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
                 
                 let is_directory = entry_path.is_dir();
                 
+                if let Some(rules) = &ignore_rules {
+                    if rules.is_ignored(&entry_path, is_directory) {
+                        continue;
+                    }
+                }
+                
                 let metadata = dir_entry.metadata().ok();
                 let size = if is_directory {
                     None
@@ -0,0 +1,16 @@
+use crate::commands::search::get_db_path;
+use crate::models::graph::{Graph, GraphFilter};
+use crate::services::graph;
+use crate::services::index_service::IndexService;
+use tauri::{command, AppHandle};
+
+#[command]
+pub fn get_graph(app: AppHandle, filter: Option<GraphFilter>) -> Result<Graph, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    graph::build(&conn, &filter.unwrap_or_default())
+}
@@ -1,3 +1,6 @@
 pub mod file;
+pub mod graph;
+pub mod notes;
+pub mod query;
 pub mod search;
-
+pub mod tasks;
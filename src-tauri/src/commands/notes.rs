@@ -0,0 +1,124 @@
+use crate::commands::search::get_db_path;
+use crate::models::file::{ModifiedFile, RenameResult};
+use crate::models::note::{Backlink, NoteRef, Property, PropertyKey, RenderedNote, TagNode, UnlinkedMention};
+use crate::services::index_service::IndexService;
+use crate::services::{index_roots, links, mentions, properties, rename, render, tags};
+use tauri::{command, AppHandle};
+
+#[command]
+pub fn get_properties(app: AppHandle, path: String) -> Result<Vec<Property>, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    properties::for_path(&conn, &path)
+}
+
+#[command]
+pub fn list_property_keys(app: AppHandle) -> Result<Vec<PropertyKey>, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    properties::list_keys(&conn)
+}
+
+#[command]
+pub fn list_tags(app: AppHandle) -> Result<Vec<TagNode>, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    tags::list(&conn)
+}
+
+/// Notes tagged with `tag`; nested tags are included unless `include_nested` is false.
+#[command]
+pub fn files_by_tag(
+    app: AppHandle,
+    tag: String,
+    include_nested: Option<bool>,
+) -> Result<Vec<NoteRef>, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    tags::files_by_tag(&conn, &tag, include_nested.unwrap_or(true))
+}
+
+#[command]
+pub fn get_backlinks(app: AppHandle, path: String) -> Result<Vec<Backlink>, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    links::backlinks(&conn, &path)
+}
+
+/// Notes that name the note at `path` in plain text without linking to it.
+#[command]
+pub fn get_unlinked_mentions(app: AppHandle, path: String) -> Result<Vec<UnlinkedMention>, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    mentions::unlinked_mentions(&conn, &path)
+}
+
+/// Turns one unlinked mention of `path` in `source_path` into a wikilink.
+#[command]
+pub fn link_mention(
+    app: AppHandle,
+    path: String,
+    source_path: String,
+    line: usize,
+    start: usize,
+    end: usize,
+) -> Result<ModifiedFile, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    let source_path = index_roots::existing_path_inside(&conn, &source_path)?;
+    mentions::link_mention(&conn, &path, &source_path, line, start, end)
+}
+
+/// Moves a file or folder and rewrites links to and from it across the vault.
+#[command]
+pub fn rename_path(app: AppHandle, from: String, to: String) -> Result<RenameResult, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    let from = index_roots::existing_path_inside(&conn, &from)?;
+    let to = index_roots::new_path_inside(&conn, &to)?;
+    rename::rename_path(&conn, &from, &to)
+}
+
+/// The note at `path` with embedded notes, sections and blocks expanded.
+#[command]
+pub fn render_note(app: AppHandle, path: String) -> Result<RenderedNote, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    render::render_note(&conn, &path)
+}
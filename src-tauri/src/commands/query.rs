@@ -0,0 +1,18 @@
+use crate::commands::search::get_db_path;
+use crate::models::query::QueryResult;
+use crate::services::index_service::IndexService;
+use crate::services::query;
+use tauri::{command, AppHandle};
+
+/// Runs a `TABLE ... FROM ... WHERE ... SORT ...` or `LIST ...` query over
+/// the indexed notes.
+#[command]
+pub fn run_query(app: AppHandle, source: String) -> Result<QueryResult, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    query::run(&conn, &source, None)
+}
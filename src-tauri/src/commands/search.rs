@@ -1,16 +1,23 @@
+use crate::models::index::{IndexCheckReport, IndexJob, IndexRepairReport, IndexedRoot};
+use crate::models::note::{LinkCandidate, WikilinkTarget};
 use crate::models::search::{ContentMatch, FileMatch};
+use crate::services::index_integrity;
+use crate::services::index_queue::{self, PRIORITY_HIGH};
+use crate::services::index_roots;
+use crate::services::link_resolver;
+use crate::services::search;
 use crate::services::index_service::IndexService;
 use crate::services::background_indexer::BackgroundIndexer;
-use rusqlite::{params, Connection};
+use rusqlite::params;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::OnceLock;
 use tauri::{command, AppHandle, Manager};
 
 static BACKGROUND_INDEXER: OnceLock<BackgroundIndexer> = OnceLock::new();
 
 // Get database path in app data directory
-fn get_db_path(app: &AppHandle) -> Result<PathBuf, String> {
+pub(crate) fn get_db_path(app: &AppHandle) -> Result<PathBuf, String> {
     app.path()
         .app_data_dir()
         .map_err(|e| format!("Failed to get app data directory: {}", e))
@@ -35,13 +42,16 @@ pub fn init_index(app: AppHandle) -> Result<String, String> {
         .initialize()
         .map_err(|e| format!("Failed to initialize database: {}", e))?;
 
-    // Initialize background indexer
-    BACKGROUND_INDEXER.get_or_init(|| BackgroundIndexer::new());
-
-    db_path
+    let db_path_str = db_path
         .to_str()
-        .ok_or_else(|| "Invalid database path".to_string())
-        .map(|s| s.to_string())
+        .ok_or_else(|| "Invalid database path".to_string())?
+        .to_string();
+
+    // Initialize background indexer and pick up jobs from the last session
+    let indexer = BACKGROUND_INDEXER.get_or_init(BackgroundIndexer::new);
+    indexer.resume_queue(app, db_path_str.clone());
+
+    Ok(db_path_str)
 }
 
 #[command]
@@ -53,123 +63,133 @@ pub fn notify_directory_opened(app: AppHandle, directory: String) -> Result<(),
         .to_string();
 
     // Get or initialize the background indexer
-    let indexer = BACKGROUND_INDEXER.get_or_init(|| BackgroundIndexer::new());
+    let indexer = BACKGROUND_INDEXER.get_or_init(BackgroundIndexer::new);
 
     // Trigger background indexing (returns immediately!)
-    indexer.start_indexing(app, directory, db_path_str);
+    indexer.start_indexing(app, directory, db_path_str, PRIORITY_HIGH);
 
     Ok(())
 }
 
 #[command]
-pub fn index_directory(app: AppHandle, directory: String) -> Result<usize, String> {
+pub fn cancel_indexing(app: AppHandle, directory: String) -> Result<bool, String> {
+    let db_path = get_db_path(&app)?;
+    let db_path_str = db_path
+        .to_str()
+        .ok_or_else(|| "Invalid database path".to_string())?
+        .to_string();
+
+    let indexer = BACKGROUND_INDEXER.get_or_init(BackgroundIndexer::new);
+    indexer.cancel(app, &directory, db_path_str)
+}
+
+#[command]
+pub fn set_indexing_workers(workers: usize) -> Result<(), String> {
+    if workers == 0 {
+        return Err("Worker count must be at least 1".to_string());
+    }
+
+    let indexer = BACKGROUND_INDEXER.get_or_init(BackgroundIndexer::new);
+    indexer.set_worker_count(workers);
+
+    Ok(())
+}
+
+#[command]
+pub fn get_indexing_queue(app: AppHandle) -> Result<Vec<IndexJob>, String> {
     let db_path = get_db_path(&app)?;
     let service = IndexService::new(db_path);
     let conn = service
         .get_connection()
         .map_err(|e| format!("Failed to connect to database: {}", e))?;
 
-    let mut indexed_count = 0;
-    index_directory_recursive(&conn, Path::new(&directory), &mut indexed_count)?;
+    index_queue::list(&conn)
+}
+
+#[command]
+pub fn list_indexed_roots(app: AppHandle) -> Result<Vec<IndexedRoot>, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
 
-    Ok(indexed_count)
+    index_roots::list(&conn)
 }
 
-fn index_directory_recursive(
-    conn: &Connection,
-    dir: &Path,
-    count: &mut usize,
-) -> Result<(), String> {
-    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read directory: {}", e))?;
-
-    for entry in entries {
-        if let Ok(entry) = entry {
-            let path = entry.path();
-
-            if path.is_dir() {
-                // Recursively index subdirectories
-                index_directory_recursive(conn, &path, count)?;
-            } else if let Some(ext) = path.extension() {
-                // Only index .md files
-                if ext == "md" {
-                    if let Err(e) = index_file(conn, &path) {
-                        eprintln!("Failed to index {:?}: {}", path, e);
-                    } else {
-                        *count += 1;
-                    }
-                }
-            }
-        }
-    }
+#[command]
+pub fn reindex_root(app: AppHandle, root: String) -> Result<(), String> {
+    let db_path = get_db_path(&app)?;
+    let db_path_str = db_path
+        .to_str()
+        .ok_or_else(|| "Invalid database path".to_string())?
+        .to_string();
+
+    let indexer = BACKGROUND_INDEXER.get_or_init(BackgroundIndexer::new);
+    indexer.reindex(app, root, db_path_str);
 
     Ok(())
 }
 
-fn index_file(conn: &Connection, path: &Path) -> Result<(), String> {
-    let content = fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
-
-    let path_str = path
+#[command]
+pub fn forget_root(app: AppHandle, root: String) -> Result<usize, String> {
+    let db_path = get_db_path(&app)?;
+    let db_path_str = db_path
         .to_str()
-        .ok_or_else(|| "Invalid file path".to_string())?;
-
-    // Extract title from frontmatter or filename
-    let title = extract_title(&content, path);
-
-    let metadata = fs::metadata(path)
-        .map_err(|e| format!("Failed to get metadata: {}", e))?;
-
-    let modified = metadata
-        .modified()
-        .ok()
-        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-        .map(|d| d.as_secs() as i64)
-        .unwrap_or(0);
-
-    let created = metadata
-        .created()
-        .ok()
-        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-        .map(|d| d.as_secs() as i64)
-        .unwrap_or(0);
-
-    // Insert or replace file in database
-    conn.execute(
-        "INSERT OR REPLACE INTO files (path, title, content, modified, created)
-         VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![path_str, title, content, modified, created],
-    )
-    .map_err(|e| format!("Failed to insert file: {}", e))?;
-
-    // Update FTS index
-    conn.execute(
-        "INSERT INTO files_fts (rowid, path, title, content)
-         SELECT id, path, title, content FROM files WHERE path = ?1",
-        params![path_str],
-    )
-    .map_err(|e| format!("Failed to update FTS index: {}", e))?;
+        .ok_or_else(|| "Invalid database path".to_string())?
+        .to_string();
 
-    Ok(())
+    let indexer = BACKGROUND_INDEXER.get_or_init(BackgroundIndexer::new);
+    indexer.forget(app, &root, db_path_str)
 }
 
-fn extract_title(content: &str, path: &Path) -> Option<String> {
-    // Try to extract title from YAML frontmatter
-    if content.starts_with("---") {
-        if let Some(end) = content[3..].find("---") {
-            let frontmatter = &content[3..end + 3];
-            for line in frontmatter.lines() {
-                if line.trim_start().starts_with("title:") {
-                    let title = line.split(':').nth(1)?.trim();
-                    return Some(title.trim_matches('"').trim_matches('\'').to_string());
-                }
-            }
-        }
-    }
+#[command]
+pub fn check_index(app: AppHandle) -> Result<IndexCheckReport, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    index_integrity::check(&conn)
+}
+
+#[command]
+pub fn repair_index(app: AppHandle) -> Result<IndexRepairReport, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
 
-    // Fallback to filename
-    path.file_stem()
-        .and_then(|s| s.to_str())
-        .map(|s| s.to_string())
+    index_integrity::repair(&conn)
+}
+
+/// Changes how `root` is walked and re-crawls it with the new policy.
+#[command]
+pub fn set_root_options(
+    app: AppHandle,
+    root: String,
+    follow_symlinks: bool,
+    max_depth: Option<usize>,
+) -> Result<(), String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path.clone());
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    index_roots::set_options(&conn, &root, follow_symlinks, max_depth)?;
+
+    let db_path_str = db_path
+        .to_str()
+        .ok_or_else(|| "Invalid database path".to_string())?
+        .to_string();
+
+    let indexer = BACKGROUND_INDEXER.get_or_init(BackgroundIndexer::new);
+    indexer.reindex(app, root, db_path_str);
+
+    Ok(())
 }
 
 #[command]
@@ -184,30 +204,42 @@ pub fn search_files(app: AppHandle, query: String) -> Result<Vec<FileMatch>, Str
     
     let mut stmt = conn
         .prepare(
-            "SELECT path, title FROM files
-             WHERE LOWER(path) LIKE ?1 OR LOWER(title) LIKE ?1
+            "SELECT f.path, f.title,
+                 (SELECT a.alias FROM aliases a WHERE a.file_id = f.id AND a.alias_key LIKE ?2 LIMIT 1)
+             FROM files f
+             WHERE LOWER(f.path) LIKE ?1 OR LOWER(f.title) LIKE ?1
+                 OR EXISTS (SELECT 1 FROM aliases a WHERE a.file_id = f.id AND a.alias_key LIKE ?2)
              LIMIT 50",
         )
         .map_err(|e| format!("Failed to prepare statement: {}", e))?;
 
     let pattern = format!("%{}%", query_lower);
+    // Aliases are stored Unicode-normalized
+    let alias_pattern = format!("%{}%", link_resolver::normalize_key(&query));
     
     let matches = stmt
-        .query_map(params![pattern], |row| {
+        .query_map(params![pattern, alias_pattern], |row| {
             let path: String = row.get(0)?;
             let title: Option<String> = row.get(1)?;
+            let alias: Option<String> = row.get(2)?;
             
-            // Simple scoring: higher if match is earlier in string
-            let score = if path.to_lowercase().contains(&query_lower) {
-                100.0
+            // Path matches rank first, then title; aliases are only a fallback
+            let title_match = title
+                .as_ref()
+                .is_some_and(|t| t.to_lowercase().contains(&query_lower));
+            let (score, matched_alias) = if path.to_lowercase().contains(&query_lower) {
+                (100.0, None)
+            } else if title_match {
+                (50.0, None)
             } else {
-                50.0
+                (25.0, alias)
             };
 
             Ok(FileMatch {
                 path,
                 title,
                 score,
+                matched_alias,
             })
         })
         .map_err(|e| format!("Failed to execute query: {}", e))?;
@@ -222,77 +254,54 @@ pub fn search_files(app: AppHandle, query: String) -> Result<Vec<FileMatch>, Str
     Ok(results)
 }
 
+/// Content search. Besides words, the query may use quoted phrases,
+/// `-negation`, `OR`, brackets and `path:`, `title:`, `tag:` and
+/// `modified:>2026-01-01` filters. `tags` restricts results to notes carrying
+/// every given tag (or a tag nested under it).
 #[command]
-pub fn search_content(app: AppHandle, query: String) -> Result<Vec<ContentMatch>, String> {
+pub fn search_content(
+    app: AppHandle,
+    query: String,
+    tags: Option<Vec<String>>,
+) -> Result<Vec<ContentMatch>, String> {
     let db_path = get_db_path(&app)?;
     let service = IndexService::new(db_path);
     let conn = service
         .get_connection()
         .map_err(|e| format!("Failed to connect to database: {}", e))?;
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT f.path, f.title, snippet(files_fts, 2, '<mark>', '</mark>', '...', 32) as snippet
-             FROM files_fts
-             JOIN files f ON files_fts.rowid = f.id
-             WHERE files_fts MATCH ?1
-             LIMIT 50",
-        )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-    let matches = stmt
-        .query_map(params![query], |row| {
-            let path: String = row.get(0)?;
-            let title: Option<String> = row.get(1)?;
-            let snippet: String = row.get(2)?;
-
-            Ok(ContentMatch {
-                path,
-                title,
-                snippet,
-                matches: 1,
-            })
-        })
-        .map_err(|e| format!("Failed to execute query: {}", e))?;
-
-    let results: Vec<ContentMatch> = matches.filter_map(|m| m.ok()).collect();
-
-    Ok(results)
+    search::content(&conn, &query, &tags.unwrap_or_default())
 }
 
+/// Resolves `[[Target]]`, `[[Target#Heading]]` or `[[Target#^block]]`,
+/// written in the note at `current_path`, to a note and, if the link has one,
+/// the range of its anchor. `[[#Heading]]` points into that note itself.
 #[command]
-pub fn resolve_wikilink(app: AppHandle, link: String, _current_dir: String) -> Result<Option<String>, String> {
+pub fn resolve_wikilink(
+    app: AppHandle,
+    link: String,
+    current_path: String,
+) -> Result<Option<WikilinkTarget>, String> {
     let db_path = get_db_path(&app)?;
     let service = IndexService::new(db_path);
     let conn = service
         .get_connection()
         .map_err(|e| format!("Failed to connect to database: {}", e))?;
 
-    // Extract target from link (handle [[Target]] or [[Target|Alias]])
-    let target = link.split('|').next().unwrap_or(&link).trim();
-
-    // Try exact title match first
-    let mut stmt = conn
-        .prepare("SELECT path FROM files WHERE title = ?1 LIMIT 1")
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-    let result: Result<Option<String>, _> = stmt.query_row(params![target], |row| row.get(0));
-
-    if let Ok(Some(path)) = result {
-        return Ok(Some(path));
-    }
-
-    // Try fuzzy match on path
-    let mut stmt = conn
-        .prepare("SELECT path FROM files WHERE path LIKE ?1 LIMIT 1")
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    link_resolver::resolve_wikilink(&conn, &link, &current_path)
+}
 
-    let pattern = format!("%{}%", target);
-    let result: Result<Option<String>, _> = stmt.query_row(params![pattern], |row| row.get(0));
+#[command]
+pub fn resolve_wikilink_candidates(
+    app: AppHandle,
+    link: String,
+    current_path: String,
+) -> Result<Vec<LinkCandidate>, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
 
-    match result {
-        Ok(Some(path)) => Ok(Some(path)),
-        _ => Ok(None),
-    }
+    link_resolver::wikilink_candidates(&conn, &link, &current_path)
 }
-
@@ -0,0 +1,32 @@
+use crate::commands::search::get_db_path;
+use crate::models::file::ModifiedFile;
+use crate::models::task::{Task, TaskFilter};
+use crate::services::index_roots;
+use crate::services::index_service::IndexService;
+use crate::services::tasks;
+use tauri::{command, AppHandle};
+
+#[command]
+pub fn query_tasks(app: AppHandle, filter: Option<TaskFilter>) -> Result<Vec<Task>, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    tasks::query(&conn, &filter.unwrap_or_default())
+}
+
+/// Completes or reopens the task on `line` (1-based) of the note at `path`,
+/// provided that line still holds a task with the text `text`.
+#[command]
+pub fn toggle_task(app: AppHandle, path: String, line: usize, text: String) -> Result<ModifiedFile, String> {
+    let db_path = get_db_path(&app)?;
+    let service = IndexService::new(db_path);
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    let path = index_roots::existing_path_inside(&conn, &path)?;
+    tasks::toggle(&conn, &path, line, &text)
+}
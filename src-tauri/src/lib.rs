@@ -4,7 +4,19 @@ mod services;
 mod utils;
 
 use commands::file::{get_home_directory, read_directory, read_file, write_file, create_file, create_directory};
-use commands::search::{init_index, notify_directory_opened, search_files, search_content, resolve_wikilink};
+use commands::graph::get_graph;
+use commands::notes::{
+    files_by_tag, get_backlinks, get_properties, get_unlinked_mentions, link_mention,
+    list_property_keys, list_tags, rename_path, render_note,
+};
+use commands::query::run_query;
+use commands::search::{
+    init_index, notify_directory_opened, cancel_indexing, set_indexing_workers, get_indexing_queue,
+    list_indexed_roots, reindex_root, forget_root,
+    set_root_options, check_index, repair_index, search_files, search_content, resolve_wikilink,
+    resolve_wikilink_candidates,
+};
+use commands::tasks::{query_tasks, toggle_task};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -19,9 +31,32 @@ pub fn run() {
             create_directory,
             init_index,
             notify_directory_opened,
+            cancel_indexing,
+            set_indexing_workers,
+            get_indexing_queue,
+            list_indexed_roots,
+            reindex_root,
+            forget_root,
+            set_root_options,
+            check_index,
+            repair_index,
             search_files,
             search_content,
-            resolve_wikilink
+            resolve_wikilink,
+            resolve_wikilink_candidates,
+            get_properties,
+            list_property_keys,
+            list_tags,
+            files_by_tag,
+            get_backlinks,
+            get_unlinked_mentions,
+            link_mention,
+            rename_path,
+            render_note,
+            get_graph,
+            query_tasks,
+            toggle_task,
+            run_query
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
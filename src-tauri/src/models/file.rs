@@ -27,3 +27,17 @@ impl FileEntry {
     }
 }
 
+/// A file rewritten by a link edit, with what it contained before.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifiedFile {
+    pub path: String,
+    pub previous_content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameResult {
+    pub from: String,
+    pub to: String,
+    /// Notes whose links were rewritten, at their paths after the move
+    pub modified_files: Vec<ModifiedFile>,
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphNodeKind {
+    Note,
+    Tag,
+    Attachment,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// File path for notes and indexed attachments, `tag:<name>` for tags and
+    /// `attachment:<target>` for embeds of files that aren't indexed
+    pub id: String,
+    pub kind: GraphNodeKind,
+    pub label: String,
+    pub degree: usize,
+    /// Nodes sharing a component id are connected by some path of edges
+    pub component: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    /// `wikilink`, `embed`, `markdown` or `tag`
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphFilter {
+    /// Only notes under this folder
+    pub folder: Option<String>,
+    /// Only notes with this tag or a tag nested under it
+    pub tag: Option<String>,
+    /// Local graph: only nodes within `depth` links of this note
+    pub center: Option<String>,
+    /// Hops around `center`, 1 if not given
+    pub depth: Option<usize>,
+    #[serde(default)]
+    pub include_tags: bool,
+    #[serde(default)]
+    pub include_attachments: bool,
+}
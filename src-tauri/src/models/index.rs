@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChangedEvent {
+    pub path: String,
+    pub kind: FileChangeKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexJob {
+    pub root: String,
+    pub priority: i64,
+    pub status: String,
+    pub enqueued_at: i64,
+    pub started_at: Option<i64>,
+    /// Rebuild every file instead of skipping unchanged ones
+    pub force: bool,
+}
+
+/// Payload of `indexing-complete`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexStats {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+    /// Symlinks, cycles and too-deep folders the crawler did not enter
+    pub skipped: Vec<SkippedEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedEntry {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Payload of `indexing-progress`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexProgress {
+    pub root: String,
+    pub done: usize,
+    pub total: usize,
+    pub current_path: String,
+    pub eta_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedRoot {
+    pub path: String,
+    pub added_at: i64,
+    pub last_indexed_at: Option<i64>,
+    pub file_count: i64,
+    pub follow_symlinks: bool,
+    /// `None` uses the default depth limit
+    pub max_depth: Option<i64>,
+}
+
+/// Result of `check_index`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexCheckReport {
+    /// FTS5 `integrity-check` passed, including agreement with `files`
+    pub fts_ok: bool,
+    pub fts_error: Option<String>,
+    pub file_rows: i64,
+    /// Documents the full-text index holds, which drift from `file_rows`
+    /// when a write was interrupted
+    pub fts_rows: i64,
+    /// Indexed paths that no longer exist on disk
+    pub missing_files: Vec<String>,
+    /// Indexed paths that aren't under any known or queued root. Repair
+    /// leaves them alone: they can come from a first crawl that was
+    /// cancelled before its root was recorded
+    pub outside_roots: Vec<String>,
+}
+
+/// Result of `repair_index`; `after` is a fresh check of the repaired index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexRepairReport {
+    pub removed_missing: usize,
+    pub fts_rebuilt: bool,
+    pub after: IndexCheckReport,
+}
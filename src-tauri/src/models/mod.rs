@@ -1,3 +1,7 @@
 pub mod file;
+pub mod graph;
+pub mod index;
+pub mod note;
+pub mod query;
 pub mod search;
-
+pub mod task;
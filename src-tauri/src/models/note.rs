@@ -0,0 +1,205 @@
+use crate::models::query::QueryBlock;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PropertyType {
+    String,
+    Number,
+    Bool,
+    Date,
+    List,
+    Object,
+}
+
+impl PropertyType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PropertyType::String => "string",
+            PropertyType::Number => "number",
+            PropertyType::Bool => "bool",
+            PropertyType::Date => "date",
+            PropertyType::List => "list",
+            PropertyType::Object => "object",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "number" => PropertyType::Number,
+            "bool" => PropertyType::Bool,
+            "date" => PropertyType::Date,
+            "list" => PropertyType::List,
+            "object" => PropertyType::Object,
+            _ => PropertyType::String,
+        }
+    }
+}
+
+/// One frontmatter key of a note.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Property {
+    pub key: String,
+    pub value_type: PropertyType,
+    /// Dates stay in their original string form
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyKey {
+    pub key: String,
+    /// Most common type of the key across the vault
+    pub value_type: PropertyType,
+    pub file_count: i64,
+}
+
+/// A note in a result list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteRef {
+    pub path: String,
+    pub title: Option<String>,
+}
+
+/// One level of the tag hierarchy; `#area/project` is `project` under `area`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagNode {
+    /// Last path segment
+    pub name: String,
+    /// Full tag without the `#`
+    pub tag: String,
+    /// Files tagged with exactly this tag
+    pub count: i64,
+    /// Files tagged with this tag or any tag nested under it
+    pub total_count: i64,
+    pub children: Vec<TagNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    Wikilink,
+    Embed,
+    Markdown,
+}
+
+impl LinkKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkKind::Wikilink => "wikilink",
+            LinkKind::Embed => "embed",
+            LinkKind::Markdown => "markdown",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "embed" => LinkKind::Embed,
+            "markdown" => LinkKind::Markdown,
+            _ => LinkKind::Wikilink,
+        }
+    }
+}
+
+/// A link pointing at the requested note.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Backlink {
+    pub source_path: String,
+    pub source_title: Option<String>,
+    /// Target as written in the source note
+    pub target: String,
+    pub kind: LinkKind,
+    pub line: i64,
+    pub context: String,
+}
+
+/// Part of a file: 1-based inclusive lines and a byte range with an
+/// exclusive end.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SourceRange {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub start_byte: usize,
+    pub end_byte: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnchorKind {
+    Heading,
+    Block,
+}
+
+/// The `#Heading` or `#^block-id` part of a link, located in its note.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkAnchor {
+    pub kind: AnchorKind,
+    /// Heading text or block id as stored in the note
+    pub name: String,
+    pub range: SourceRange,
+}
+
+/// A plain-text occurrence of a note's name, title or alias in another note.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnlinkedMention {
+    pub source_path: String,
+    pub source_title: Option<String>,
+    pub line: usize,
+    /// Byte range of the mention within the line
+    pub start: usize,
+    pub end: usize,
+    /// The mention as written
+    pub text: String,
+    pub context: String,
+}
+
+/// Where a wikilink points: a note and optionally a place inside it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WikilinkTarget {
+    pub path: String,
+    pub anchor: Option<LinkAnchor>,
+}
+
+/// One of the notes an ambiguous link could mean.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkCandidate {
+    pub path: String,
+    /// Folder steps from the linking note's folder
+    pub distance: usize,
+    /// Set when the link matched a frontmatter alias rather than the file name
+    pub alias: Option<String>,
+}
+
+/// A note with its `![[embeds]]` expanded, for the preview.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedNote {
+    pub path: String,
+    pub content: String,
+    /// Where each stretch of `content` came from, in order and without gaps,
+    /// so an edit in the preview can be written back to the right file
+    pub source_map: Vec<SourceSegment>,
+    /// Embeds that were left as written
+    pub skipped_embeds: Vec<SkippedEmbed>,
+    /// Results of the query blocks, in order
+    pub query_blocks: Vec<QueryBlock>,
+}
+
+/// Bytes `start..end` of the rendered content are the bytes starting at
+/// `source_start` in `path`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceSegment {
+    pub start: usize,
+    pub end: usize,
+    pub path: String,
+    pub source_start: usize,
+    /// 0 for the rendered note itself, 1 for its embeds and so on
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedEmbed {
+    /// The note containing the embed
+    pub path: String,
+    pub line: usize,
+    pub target: String,
+    pub reason: String,
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryResultKind {
+    Table,
+    List,
+}
+
+/// Result of `run_query`. Unless the query says `WITHOUT ID`, the first
+/// column is the file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub kind: QueryResultKind,
+    pub columns: Vec<String>,
+    pub rows: Vec<QueryRow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRow {
+    /// The note the row is for, even when its column is hidden
+    pub path: String,
+    /// One value per column: property values as stored, dates as `YYYY-MM-DD` text
+    pub values: Vec<serde_json::Value>,
+}
+
+/// A ```` ```query ```` block in a rendered note and what it evaluated to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryBlock {
+    /// The note the block is written in, which may be an embedded one
+    pub path: String,
+    /// Line of the opening fence in that note
+    pub line: usize,
+    /// Byte range of the block, fences included, in the rendered content
+    pub start: usize,
+    pub end: usize,
+    pub source: String,
+    pub result: Option<QueryResult>,
+    pub error: Option<String>,
+}
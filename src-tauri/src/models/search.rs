@@ -5,6 +5,8 @@ pub struct FileMatch {
     pub path: String,
     pub title: Option<String>,
     pub score: f64,
+    /// Alias the query matched, when neither the path nor the title did
+    pub matched_alias: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
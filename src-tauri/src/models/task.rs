@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    pub path: String,
+    pub line: usize,
+    /// The checkbox character: ` ` open, `x` done, or a custom status
+    pub status: String,
+    pub completed: bool,
+    /// Item text without its metadata
+    pub text: String,
+    pub heading: Option<String>,
+    /// Dates are `YYYY-MM-DD`
+    pub due: Option<String>,
+    pub scheduled: Option<String>,
+    pub start: Option<String>,
+    pub created: Option<String>,
+    pub done: Option<String>,
+    /// `highest`, `high`, `medium`, `low` or `lowest`
+    pub priority: Option<String>,
+    /// Normalized recurrence rule, e.g. `every 2 weeks when done`
+    pub recurrence: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// Criteria for `query_tasks`; unset fields don't filter.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskFilter {
+    pub completed: Option<bool>,
+    /// Only tasks in notes under this folder
+    pub folder: Option<String>,
+    /// Only tasks in this note
+    pub path: Option<String>,
+    /// Only tasks tagged with this tag or a tag nested under it
+    pub tag: Option<String>,
+    /// Only tasks with (or without) a recurrence rule
+    pub recurring: Option<bool>,
+    /// Due on or before this date: `YYYY-MM-DD`, `today`, `tomorrow`,
+    /// `yesterday`, `in 2 weeks` or `3 days ago`
+    pub due_before: Option<String>,
+    /// Due on or after this date, in the same forms as `due_before`
+    pub due_after: Option<String>,
+    pub priority: Option<String>,
+    /// Case-insensitive substring of the task text
+    pub text: Option<String>,
+    pub limit: Option<usize>,
+}
@@ -0,0 +1,39 @@
+use crate::services::link_resolver::normalize_key;
+use crate::services::links;
+use rusqlite::{params, Connection};
+use std::collections::HashSet;
+
+/// Replaces the stored aliases of a file.
+pub fn replace(conn: &Connection, file_id: i64, aliases: &[String]) -> Result<(), String> {
+    let previous: HashSet<String> = {
+        let mut stmt = conn
+            .prepare_cached("SELECT alias_key FROM aliases WHERE file_id = ?1")
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        let rows = stmt
+            .query_map(params![file_id], |row| row.get(0))
+            .map_err(|e| format!("Failed to execute query: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+    let added: Vec<String> = aliases
+        .iter()
+        .map(|alias| normalize_key(alias))
+        .filter(|key| !previous.contains(key))
+        .collect();
+    links::mark_stale_names(conn, &added)?;
+
+    conn.execute("DELETE FROM aliases WHERE file_id = ?1", params![file_id])
+        .map_err(|e| format!("Failed to clear aliases: {}", e))?;
+
+    let mut stmt = conn
+        .prepare_cached("INSERT INTO aliases (file_id, alias, alias_key) VALUES (?1, ?2, ?3)")
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    for alias in aliases {
+        stmt.execute(params![file_id, alias, normalize_key(alias)])
+            .map_err(|e| format!("Failed to store alias: {}", e))?;
+    }
+
+    Ok(())
+}
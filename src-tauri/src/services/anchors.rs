@@ -0,0 +1,145 @@
+use crate::models::note::{AnchorKind, LinkAnchor, SourceRange};
+use crate::services::note_parser::{ParsedBlock, ParsedHeading, ParsedNote};
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Replaces the stored headings and block anchors of a file.
+pub fn replace(
+    conn: &Connection,
+    file_id: i64,
+    headings: &[ParsedHeading],
+    blocks: &[ParsedBlock],
+) -> Result<(), String> {
+    conn.execute("DELETE FROM headings WHERE file_id = ?1", params![file_id])
+        .map_err(|e| format!("Failed to clear headings: {}", e))?;
+    conn.execute("DELETE FROM blocks WHERE file_id = ?1", params![file_id])
+        .map_err(|e| format!("Failed to clear blocks: {}", e))?;
+
+    let mut stmt = conn
+        .prepare_cached(
+            "INSERT INTO headings (file_id, level, text, text_key, start_line, end_line, start_byte, end_byte)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    for heading in headings {
+        let r = &heading.range;
+        stmt.execute(params![
+            file_id,
+            heading.level as i64,
+            heading.text,
+            heading_key(&heading.text),
+            r.start_line as i64,
+            r.end_line as i64,
+            r.start_byte as i64,
+            r.end_byte as i64
+        ])
+        .map_err(|e| format!("Failed to store heading: {}", e))?;
+    }
+
+    let mut stmt = conn
+        .prepare_cached(
+            "INSERT INTO blocks (file_id, block_id, start_line, end_line, start_byte, end_byte)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    for block in blocks {
+        let r = &block.range;
+        stmt.execute(params![
+            file_id,
+            block.id,
+            r.start_line as i64,
+            r.end_line as i64,
+            r.start_byte as i64,
+            r.end_byte as i64
+        ])
+        .map_err(|e| format!("Failed to store block: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Splits `Note#Heading`, `Note#Parent#Child` or `Note#^block` into the note
+/// part and the innermost anchor.
+pub fn split_link(target: &str) -> (&str, Option<&str>) {
+    match target.split_once('#') {
+        Some((note, anchors)) => {
+            let anchor = anchors.rsplit('#').next().unwrap_or(anchors).trim();
+            (note.trim(), (!anchor.is_empty()).then_some(anchor))
+        }
+        None => (target.trim(), None),
+    }
+}
+
+/// Headings match case-insensitively and ignoring runs of whitespace.
+fn heading_key(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Locates `anchor` (heading text or `^block-id`) in the note at `path`.
+/// Duplicate headings resolve to the first one.
+pub fn find(conn: &Connection, path: &str, anchor: &str) -> Result<Option<LinkAnchor>, String> {
+    let to_range = |row: &rusqlite::Row| -> rusqlite::Result<(String, SourceRange)> {
+        Ok((
+            row.get(0)?,
+            SourceRange {
+                start_line: row.get::<_, i64>(1)? as usize,
+                end_line: row.get::<_, i64>(2)? as usize,
+                start_byte: row.get::<_, i64>(3)? as usize,
+                end_byte: row.get::<_, i64>(4)? as usize,
+            },
+        ))
+    };
+
+    let (kind, found) = match anchor.strip_prefix('^') {
+        Some(block_id) => {
+            let found = conn
+                .query_row(
+                    "SELECT b.block_id, b.start_line, b.end_line, b.start_byte, b.end_byte
+                     FROM blocks b JOIN files f ON f.id = b.file_id
+                     WHERE f.path = ?1 AND b.block_id = ?2
+                     ORDER BY b.start_line LIMIT 1",
+                    params![path, block_id],
+                    to_range,
+                )
+                .optional();
+            (AnchorKind::Block, found)
+        }
+        None => {
+            let found = conn
+                .query_row(
+                    "SELECT h.text, h.start_line, h.end_line, h.start_byte, h.end_byte
+                     FROM headings h JOIN files f ON f.id = h.file_id
+                     WHERE f.path = ?1 AND h.text_key = ?2
+                     ORDER BY h.start_line LIMIT 1",
+                    params![path, heading_key(anchor)],
+                    to_range,
+                )
+                .optional();
+            (AnchorKind::Heading, found)
+        }
+    };
+
+    let found = found.map_err(|e| format!("Failed to look up anchor: {}", e))?;
+    Ok(found.map(|(name, range)| LinkAnchor { kind, name, range }))
+}
+
+/// Like `find`, but against a freshly parsed note instead of the index, for
+/// text that may have changed since it was last indexed.
+pub fn find_in(note: &ParsedNote, anchor: &str) -> Option<SourceRange> {
+    match anchor.strip_prefix('^') {
+        Some(block_id) => note
+            .blocks
+            .iter()
+            .find(|block| block.id == block_id)
+            .map(|block| block.range),
+        None => {
+            let key = heading_key(anchor);
+            note.headings
+                .iter()
+                .find(|heading| heading_key(&heading.text) == key)
+                .map(|heading| heading.range)
+        }
+    }
+}
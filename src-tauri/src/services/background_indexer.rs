@@ -1,17 +1,37 @@
+use crate::models::index::{IndexProgress, IndexStats};
+use crate::services::content_extractor::registry;
+use crate::services::dir_walker::walk_within;
+use crate::services::file_indexer::{
+    load_stored_under, prepare_file, remove_path, write_prepared, IndexOutcome, PreparedFile,
+};
+use crate::services::file_watcher::FileWatcher;
+use crate::services::index_queue::{self, PRIORITY_LOW, PRIORITY_NORMAL};
+use crate::services::index_roots;
+use crate::services::links;
 use crate::services::index_service::IndexService;
-use std::collections::HashSet;
-use std::fs;
-use std::path::Path;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 
 const MAX_CONCURRENT_INDEXING: usize = 2;
+// How long `forget` waits for a running crawl of the same root to stop
+const FORGET_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub struct BackgroundIndexer {
     indexed_dirs: Arc<Mutex<HashSet<String>>>,
     active_threads: Arc<AtomicUsize>,
+    file_watcher: Arc<FileWatcher>,
+    // Cancellation flags for running jobs, keyed by root
+    cancel_flags: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    // Reader threads per crawl
+    worker_count: Arc<AtomicUsize>,
+    // Set once the previous session's queue has been picked up
+    resumed: AtomicBool,
 }
 
 impl BackgroundIndexer {
@@ -19,188 +39,551 @@ impl BackgroundIndexer {
         Self {
             indexed_dirs: Arc::new(Mutex::new(HashSet::new())),
             active_threads: Arc::new(AtomicUsize::new(0)),
+            file_watcher: Arc::new(FileWatcher::new()),
+            cancel_flags: Arc::new(Mutex::new(HashMap::new())),
+            worker_count: Arc::new(AtomicUsize::new(default_worker_count())),
+            resumed: AtomicBool::new(false),
         }
     }
 
-    pub fn start_indexing(&self, app_handle: AppHandle, directory: String, db_path: String) {
-        let indexed_dirs = self.indexed_dirs.clone();
-        let active_threads = self.active_threads.clone();
+    /// Sets the number of reader threads used by crawls started from now on.
+    pub fn set_worker_count(&self, count: usize) {
+        self.worker_count.store(count.max(1), Ordering::SeqCst);
+    }
 
+    /// Queues a directory for background indexing and starts workers if
+    /// there is capacity. Returns immediately.
+    pub fn start_indexing(&self, app_handle: AppHandle, directory: String, db_path: String, priority: i64) {
         // Check if already indexed
         {
-            let dirs = indexed_dirs.lock().unwrap();
+            let dirs = self.indexed_dirs.lock().unwrap();
             if dirs.contains(&directory) {
                 println!("Directory already indexed: {}", directory);
                 return;
             }
         }
 
+        self.enqueue(app_handle, directory, db_path, priority, false);
+    }
+
+    /// Queues a full rebuild of `directory`, re-extracting every file even
+    /// if it looks unchanged.
+    pub fn reindex(&self, app_handle: AppHandle, directory: String, db_path: String) {
+        self.indexed_dirs.lock().unwrap().remove(&directory);
+        self.enqueue(app_handle, directory, db_path, PRIORITY_NORMAL, true);
+    }
+
+    fn enqueue(&self, app_handle: AppHandle, directory: String, db_path: String, priority: i64, force: bool) {
+        let ctx = self.worker_context(app_handle, db_path);
+        if let Err(e) = with_connection(&ctx.db_path, |conn| index_queue::enqueue(conn, &directory, priority, force)) {
+            eprintln!("{}", e);
+            return;
+        }
+        emit_queue(&ctx);
+
+        dispatch(ctx);
+    }
+
+    /// Re-queues jobs left over from a previous session, plus a catch-up
+    /// crawl of every known root for edits made while the app was closed.
+    /// Only the first call per process does anything: a job marked running
+    /// after that really is running, not interrupted.
+    pub fn resume_queue(&self, app_handle: AppHandle, db_path: String) {
+        if self.resumed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let ctx = self.worker_context(app_handle, db_path);
+        match with_connection(&ctx.db_path, index_queue::reset_interrupted) {
+            Ok(0) => {}
+            Ok(count) => println!("Resuming {} interrupted indexing job(s)", count),
+            Err(e) => eprintln!("{}", e),
+        }
+
+        let result = with_connection(&ctx.db_path, |conn| {
+            for root in index_roots::list(conn)? {
+                index_queue::enqueue(conn, &root.path, PRIORITY_LOW, false)?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("{}", e);
+        }
+        emit_queue(&ctx);
+
+        dispatch(ctx);
+    }
+
+    /// Stops indexing and watching `directory` and drops everything under it
+    /// from the index. Returns the number of files removed.
+    pub fn forget(&self, app_handle: AppHandle, directory: &str, db_path: String) -> Result<usize, String> {
+        if self.signal_cancel(directory) {
+            // Let the worker finish its current batch so nothing is written after the delete
+            let deadline = Instant::now() + FORGET_TIMEOUT;
+            while self.cancel_flags.lock().unwrap().contains_key(directory) {
+                if Instant::now() >= deadline {
+                    return Err(format!("Timed out waiting for indexing of {} to stop", directory));
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+        }
+
+        self.file_watcher.unwatch(directory);
+        self.indexed_dirs.lock().unwrap().remove(directory);
+
+        let ctx = self.worker_context(app_handle, db_path);
+        let removed = with_connection(&ctx.db_path, |conn| index_roots::forget(conn, directory))?;
+        emit_queue(&ctx);
+
+        Ok(removed)
+    }
+
+    /// Cancels a queued or running job. Returns false if nothing was
+    /// queued or running for `directory`.
+    pub fn cancel(&self, app_handle: AppHandle, directory: &str, db_path: String) -> Result<bool, String> {
+        let ctx = self.worker_context(app_handle, db_path);
+
+        if self.signal_cancel(directory) {
+            return Ok(true);
+        }
+
+        if with_connection(&ctx.db_path, |conn| index_queue::remove_pending(conn, directory))? {
+            emit_cancelled(&ctx, directory);
+            emit_queue(&ctx);
+            return Ok(true);
+        }
+
+        // The job may have been claimed between the two checks above
+        Ok(self.signal_cancel(directory))
+    }
+
+    fn signal_cancel(&self, directory: &str) -> bool {
+        match self.cancel_flags.lock().unwrap().get(directory) {
+            Some(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn worker_context(&self, app_handle: AppHandle, db_path: String) -> WorkerContext {
+        WorkerContext {
+            indexed_dirs: self.indexed_dirs.clone(),
+            active_threads: self.active_threads.clone(),
+            file_watcher: self.file_watcher.clone(),
+            cancel_flags: self.cancel_flags.clone(),
+            worker_count: self.worker_count.clone(),
+            app_handle,
+            db_path,
+        }
+    }
+}
+
+/// Everything a worker thread needs, so it can pick up the next job itself.
+#[derive(Clone)]
+struct WorkerContext {
+    indexed_dirs: Arc<Mutex<HashSet<String>>>,
+    active_threads: Arc<AtomicUsize>,
+    file_watcher: Arc<FileWatcher>,
+    cancel_flags: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    worker_count: Arc<AtomicUsize>,
+    app_handle: AppHandle,
+    db_path: String,
+}
+
+/// Starts workers for queued jobs until the concurrency limit is reached.
+fn dispatch(ctx: WorkerContext) {
+    loop {
         // Atomically check and increment the thread counter (prevents TOCTOU race)
-        let prev_threads = active_threads.fetch_add(1, Ordering::SeqCst);
+        let prev_threads = ctx.active_threads.fetch_add(1, Ordering::SeqCst);
         if prev_threads >= MAX_CONCURRENT_INDEXING {
-            // Undo the increment since we're not spawning
-            active_threads.fetch_sub(1, Ordering::SeqCst);
+            // Undo the increment; a finishing worker will dispatch the rest
+            ctx.active_threads.fetch_sub(1, Ordering::SeqCst);
+            return;
+        }
+
+        let job = match with_connection(&ctx.db_path, index_queue::claim_next) {
+            Ok(Some(job)) => job,
+            Ok(None) => {
+                ctx.active_threads.fetch_sub(1, Ordering::SeqCst);
+                return;
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                ctx.active_threads.fetch_sub(1, Ordering::SeqCst);
+                return;
+            }
+        };
+        // Registered before the thread starts so a cancel can't slip past it
+        let control = CrawlControl {
+            app_handle: Some(ctx.app_handle.clone()),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            workers: ctx.worker_count.load(Ordering::SeqCst),
+            force: job.force,
+        };
+        ctx.cancel_flags
+            .lock()
+            .unwrap()
+            .insert(job.root.clone(), control.cancelled.clone());
+        emit_queue(&ctx);
+
+        let worker_ctx = ctx.clone();
+        thread::spawn(move || run_job(worker_ctx, job.root, control));
+    }
+}
+
+fn run_job(ctx: WorkerContext, directory: String, control: CrawlControl) {
+    println!("Background indexing started for: {} [{} active]",
+        directory, ctx.active_threads.load(Ordering::SeqCst));
+
+    let mut guard = JobGuard {
+        ctx: ctx.clone(),
+        directory: directory.clone(),
+        rerun: false,
+    };
+    let result = index_directory_in_thread(&ctx.db_path, &directory, &directory, &control);
+    // A rerun requested during the crawl is dropped along with a cancelled one
+    guard.rerun = !matches!(result, Ok(None));
+    match result {
+        Ok(Some(stats)) => {
+            ctx.indexed_dirs.lock().unwrap().insert(directory.clone());
+
+            let file_count = stats.added + stats.updated + stats.unchanged;
+            if let Err(e) = with_connection(&ctx.db_path, |conn| index_roots::record_indexed(conn, &directory, file_count)) {
+                eprintln!("{}", e);
+            }
             println!(
-                "Indexing deferred for {} (max {} concurrent operations reached)",
-                directory, MAX_CONCURRENT_INDEXING
+                "✅ Background indexing complete: {} added, {} updated, {} removed, {} unchanged",
+                stats.added, stats.updated, stats.removed, stats.unchanged
             );
-            // In production, this could be queued instead of silently dropped
-            return;
+
+            // Keep the index live for edits made outside the app
+            if let Err(e) = ctx.file_watcher.watch(ctx.app_handle.clone(), ctx.db_path.clone(), &directory) {
+                eprintln!("{}", e);
+            }
+
+            // Emit event to frontend
+            if let Err(e) = ctx.app_handle.emit("indexing-complete", stats) {
+                eprintln!("Failed to emit event: {}", e);
+            }
         }
+        Ok(None) => {
+            println!("Background indexing cancelled for: {}", directory);
+            emit_cancelled(&ctx, &directory);
+        }
+        Err(e) => {
+            eprintln!("Background indexing failed: {}", e);
+            if let Err(e) = ctx.app_handle.emit("indexing-error", e.to_string()) {
+                eprintln!("Failed to emit error event: {}", e);
+            }
+        }
+    }
+}
 
-        // Spawn background thread with resource limiting
-        thread::spawn(move || {
-            println!("Background indexing started for: {} [{} active]", 
-                directory, active_threads.load(Ordering::SeqCst));
+/// Releases a job's queue entry and worker slot however `run_job` ends,
+/// including a panic in the crawl, so one bad file can't stall the queue.
+struct JobGuard {
+    ctx: WorkerContext,
+    directory: String,
+    rerun: bool,
+}
 
-            match index_directory_in_thread(&db_path, &directory) {
-                Ok(count) => {
-                    indexed_dirs.lock().unwrap().insert(directory.clone());
-                    println!("✅ Background indexing complete: {} files", count);
+impl Drop for JobGuard {
+    fn drop(&mut self) {
+        let ctx = &self.ctx;
+        ctx.cancel_flags
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.directory);
+        match with_connection(&ctx.db_path, |conn| index_queue::finish(conn, &self.directory, self.rerun)) {
+            Ok(true) => println!("Re-queued indexing of {} for changes made while it ran", self.directory),
+            Ok(false) => {}
+            Err(e) => eprintln!("{}", e),
+        }
 
-                    // Emit event to frontend
-                    if let Err(e) = app_handle.emit("indexing-complete", count) {
-                        eprintln!("Failed to emit event: {}", e);
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Background indexing failed: {}", e);
-                    if let Err(e) = app_handle.emit("indexing-error", e.to_string()) {
-                        eprintln!("Failed to emit error event: {}", e);
-                    }
-                }
+        // Decrement active thread counter, then pick up anything still waiting
+        ctx.active_threads.fetch_sub(1, Ordering::SeqCst);
+        emit_queue(ctx);
+        dispatch(ctx.clone());
+    }
+}
+
+fn emit_cancelled(ctx: &WorkerContext, directory: &str) {
+    if let Err(e) = ctx.app_handle.emit("indexing-cancelled", directory) {
+        eprintln!("Failed to emit event: {}", e);
+    }
+}
+
+fn emit_queue(ctx: &WorkerContext) {
+    match with_connection(&ctx.db_path, index_queue::list) {
+        Ok(jobs) => {
+            if let Err(e) = ctx.app_handle.emit("indexing-queue-changed", jobs) {
+                eprintln!("Failed to emit event: {}", e);
             }
-            
-            // Decrement active thread counter when done
-            active_threads.fetch_sub(1, Ordering::SeqCst);
-        });
+        }
+        Err(e) => eprintln!("{}", e),
     }
 }
 
-fn index_directory_in_thread(db_path: &str, directory: &str) -> Result<usize, String> {
+fn with_connection<T>(
+    db_path: &str,
+    f: impl FnOnce(&rusqlite::Connection) -> Result<T, String>,
+) -> Result<T, String> {
     let service = IndexService::new(db_path.into());
     let conn = service
         .get_connection()
         .map_err(|e| format!("Failed to connect to database: {}", e))?;
+    f(&conn)
+}
 
-    let mut indexed_count = 0;
-    index_recursive(&conn, Path::new(directory), &mut indexed_count)?;
+// Minimum gap between `indexing-progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+// Rows written per transaction by the crawl writer
+const WRITE_BATCH_SIZE: usize = 500;
+// Bound on paths/results in flight between pipeline stages
+const PIPELINE_CAPACITY: usize = 256;
+
+/// Reader threads per crawl unless configured otherwise.
+pub(crate) fn default_worker_count() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(2)
+        .clamp(1, 8)
+}
 
-    Ok(indexed_count)
+/// Hooks and tuning for a crawl: progress events, cooperative cancellation
+/// and the number of reader threads.
+pub(crate) struct CrawlControl {
+    pub app_handle: Option<AppHandle>,
+    pub cancelled: Arc<AtomicBool>,
+    pub workers: usize,
+    /// Re-extract every file, ignoring stored mtimes and hashes
+    pub force: bool,
 }
 
-fn index_recursive(
-    conn: &rusqlite::Connection,
-    dir: &Path,
-    count: &mut usize,
-) -> Result<(), String> {
-    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read directory: {}", e))?;
-
-    for entry in entries {
-        if let Ok(entry) = entry {
-            let path = entry.path();
-
-            if path.is_dir() {
-                // Skip hidden directories
-                if let Some(name) = path.file_name() {
-                    if name.to_string_lossy().starts_with('.') {
-                        continue;
-                    }
-                }
-                index_recursive(conn, &path, count)?;
-            } else if let Some(ext) = path.extension() {
-                if ext == "md" {
-                    if let Err(e) = index_file(conn, &path) {
-                        eprintln!("Failed to index {:?}: {}", path, e);
-                    } else {
-                        *count += 1;
-                    }
-                }
-            }
+impl Default for CrawlControl {
+    fn default() -> Self {
+        Self {
+            app_handle: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            workers: default_worker_count(),
+            force: false,
         }
     }
-
-    Ok(())
 }
 
-fn index_file(conn: &rusqlite::Connection, path: &Path) -> Result<(), String> {
-    use rusqlite::params;
+type PreparedResult = (PathBuf, Result<PreparedFile, String>);
+
+/// Indexes everything under `directory`, which is `root` itself or a folder
+/// inside it, walked with the root's options and ignore rules. Returns
+/// `Ok(None)` if the crawl was cancelled; files indexed up to that point stay
+/// in the index.
+///
+/// The walk result is fed to a pool of reader threads that read, hash and
+/// extract files, while this thread is the single writer and commits their
+/// output in batches.
+pub(crate) fn index_directory_in_thread(
+    db_path: &str,
+    root: &str,
+    directory: &str,
+    control: &CrawlControl,
+) -> Result<Option<IndexStats>, String> {
+    let service = IndexService::new(db_path.into());
+    let conn = service
+        .get_connection()
+        .map_err(|e| format!("Failed to connect to database: {}", e))?;
+
+    // Collecting up front doubles as the pre-count for progress reporting
+    let options = index_roots::traversal_options(&conn, root)?;
+    let walked = walk_within(Path::new(root), Path::new(directory), &options, |path| {
+        registry().supports(path)
+    })?;
+    let files = walked.files;
+
+    // Workers compare against this snapshot instead of querying per file
+    let mut stored = load_stored_under(&conn, directory)?;
+    if control.force {
+        // Keep the ids so rows are updated in place, but forget the fingerprints
+        for file in stored.values_mut() {
+            file.mtime_ns = None;
+            file.content_hash = None;
+        }
+    }
+    let present: HashSet<&PathBuf> = files.iter().collect();
+    let vanished: Vec<PathBuf> = stored
+        .keys()
+        .filter(|path| !present.contains(path))
+        // Files in a folder that couldn't be read this time are kept as they were
+        .filter(|path| !walked.unreadable.iter().any(|dir| path.starts_with(dir)))
+        .cloned()
+        .collect();
+
+    let mut progress = control
+        .app_handle
+        .as_ref()
+        .map(|app_handle| ProgressReporter::new(app_handle, directory, files.len()));
+
+    let cancelled = control.cancelled.as_ref();
+    let (path_tx, path_rx) = mpsc::sync_channel::<PathBuf>(PIPELINE_CAPACITY);
+    let (result_tx, result_rx) = mpsc::sync_channel::<PreparedResult>(PIPELINE_CAPACITY);
+    let path_rx = Arc::new(Mutex::new(path_rx));
+
+    let mut stats = thread::scope(|scope| {
+        scope.spawn(move || {
+            for path in files {
+                // A send error means the writer gave up; stop feeding
+                if cancelled.load(Ordering::SeqCst) || path_tx.send(path).is_err() {
+                    break;
+                }
+            }
+        });
 
-    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        for _ in 0..control.workers.max(1) {
+            let path_rx = path_rx.clone();
+            let result_tx = result_tx.clone();
+            let stored = &stored;
+            scope.spawn(move || loop {
+                let next = path_rx.lock().unwrap().recv();
+                let Ok(path) = next else {
+                    break;
+                };
+                if cancelled.load(Ordering::SeqCst) {
+                    break;
+                }
 
-    let path_str = path
-        .to_str()
-        .ok_or_else(|| "Invalid file path".to_string())?;
+                let prepared = prepare_file(&path, stored.get(&path));
+                if result_tx.send((path, prepared)).is_err() {
+                    break;
+                }
+            });
+        }
+        // Only workers hold senders now, so the writer stops when they finish
+        drop(result_tx);
 
-    let title = extract_title(&content, path);
+        write_batches(&conn, result_rx, progress.as_mut(), cancelled)
+    })?;
 
-    let metadata = fs::metadata(path).map_err(|e| format!("Failed to get metadata: {}", e))?;
+    if cancelled.load(Ordering::SeqCst) {
+        return Ok(None);
+    }
 
-    let modified = metadata
-        .modified()
-        .ok()
-        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-        .map(|d| d.as_secs() as i64)
-        .unwrap_or(0);
+    // Only prune after a complete walk, otherwise unread folders would be dropped
+    for path in &vanished {
+        remove_path(&conn, path)?;
+    }
+    stats.removed = vanished.len();
+    links::resolve_pending(&conn)?;
+    stats.skipped = walked.skipped;
 
-    let created = metadata
-        .created()
-        .ok()
-        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-        .map(|d| d.as_secs() as i64)
-        .unwrap_or(0);
+    Ok(Some(stats))
+}
 
-    // Use transaction for atomicity of file + FTS index update
-    let tx = conn
+/// Single writer for a crawl. Each file gets its own savepoint so one bad
+/// file can't take the rest of its batch down with it.
+fn write_batches(
+    conn: &rusqlite::Connection,
+    results: Receiver<PreparedResult>,
+    mut progress: Option<&mut ProgressReporter>,
+    cancelled: &AtomicBool,
+) -> Result<IndexStats, String> {
+    let mut stats = IndexStats::default();
+    let mut tx = conn
         .unchecked_transaction()
         .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let mut batched = 0;
+
+    for (path, prepared) in results.iter() {
+        if cancelled.load(Ordering::SeqCst) {
+            // Dropping the receiver unblocks and stops the workers
+            break;
+        }
+
+        let outcome = prepared.and_then(|prepared| {
+            let sp = tx
+                .savepoint()
+                .map_err(|e| format!("Failed to start savepoint: {}", e))?;
+            let outcome = write_prepared(&sp, prepared)?;
+            sp.commit()
+                .map_err(|e| format!("Failed to commit savepoint: {}", e))?;
+            Ok(outcome)
+        });
+
+        match &outcome {
+            Ok(IndexOutcome::Added) => stats.added += 1,
+            Ok(IndexOutcome::Updated) => stats.updated += 1,
+            Ok(IndexOutcome::Unchanged) => stats.unchanged += 1,
+            Err(e) => eprintln!("Failed to index {:?}: {}", path, e),
+        }
+        if matches!(outcome, Ok(IndexOutcome::Added | IndexOutcome::Updated)) {
+            batched += 1;
+        }
+        if batched >= WRITE_BATCH_SIZE {
+            tx.commit()
+                .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            tx = conn
+                .unchecked_transaction()
+                .map_err(|e| format!("Failed to start transaction: {}", e))?;
+            batched = 0;
+        }
+
+        if let Some(progress) = progress.as_mut() {
+            progress.advance(&path);
+        }
+    }
 
-    // Insert or replace file
-    tx.execute(
-        "INSERT OR REPLACE INTO files (path, title, content, modified, created)
-         VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![path_str, title, content, modified, created],
-    )
-    .map_err(|e| format!("Failed to insert file: {}", e))?;
-
-    // Update FTS index - log errors instead of silently discarding
-    if let Err(e) = tx.execute(
-        "DELETE FROM files_fts WHERE rowid IN (SELECT id FROM files WHERE path = ?1)",
-        params![path_str],
-    ) {
-        eprintln!("FTS deletion warning for {}: {}", path_str, e);
-        // Continue anyway - INSERT below will handle it
-    }
-
-    tx.execute(
-        "INSERT INTO files_fts (rowid, path, title, content)
-         SELECT id, path, title, content FROM files WHERE path = ?1",
-        params![path_str],
-    )
-    .map_err(|e| format!("Failed to update FTS index: {}", e))?;
-
-    // Commit transaction (ensures atomicity)
+    // Whatever was written before a cancel is complete per file, so keep it
     tx.commit()
         .map_err(|e| format!("Failed to commit transaction: {}", e))?;
 
-    Ok(())
+    Ok(stats)
 }
 
-fn extract_title(content: &str, path: &Path) -> Option<String> {
-    if content.starts_with("---") {
-        if let Some(end) = content[3..].find("---") {
-            let frontmatter = &content[3..end + 3];
-            for line in frontmatter.lines() {
-                if line.trim_start().starts_with("title:") {
-                    let title = line.split(':').nth(1)?.trim();
-                    return Some(title.trim_matches('"').trim_matches('\'').to_string());
-                }
-            }
+/// Emits throttled `indexing-progress` events for one crawl.
+struct ProgressReporter<'a> {
+    app_handle: &'a AppHandle,
+    root: String,
+    total: usize,
+    done: usize,
+    started: Instant,
+    last_emit: Option<Instant>,
+}
+
+impl<'a> ProgressReporter<'a> {
+    fn new(app_handle: &'a AppHandle, root: &str, total: usize) -> Self {
+        Self {
+            app_handle,
+            root: root.to_string(),
+            total,
+            done: 0,
+            started: Instant::now(),
+            last_emit: None,
         }
     }
 
-    path.file_stem()
-        .and_then(|s| s.to_str())
-        .map(|s| s.to_string())
-}
+    fn advance(&mut self, current: &Path) {
+        self.done += 1;
 
+        let finished = self.done == self.total;
+        let due = self.last_emit.is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL);
+        if !finished && !due {
+            return;
+        }
+        self.last_emit = Some(Instant::now());
+
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let remaining = (self.total - self.done) as f64;
+        let eta_seconds = (elapsed / self.done as f64 * remaining).round() as u64;
+
+        let event = IndexProgress {
+            root: self.root.clone(),
+            done: self.done,
+            total: self.total,
+            current_path: current.to_string_lossy().to_string(),
+            eta_seconds,
+        };
+        if let Err(e) = self.app_handle.emit("indexing-progress", event) {
+            eprintln!("Failed to emit event: {}", e);
+        }
+    }
+}
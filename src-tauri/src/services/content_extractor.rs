@@ -0,0 +1,365 @@
+use crate::models::note::Property;
+use crate::services::frontmatter;
+use std::path::Path;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::OnceLock;
+
+pub const MARKDOWN_MIME: &str = "text/markdown";
+
+/// Title and searchable plain text pulled out of a file.
+pub struct ExtractedContent {
+    /// `None` falls back to the file name
+    pub title: Option<String>,
+    pub text: String,
+    /// Typed frontmatter, for formats that have it
+    pub properties: Vec<Property>,
+}
+
+/// Turns the raw bytes of one kind of file into text for `files_fts`.
+pub trait ContentExtractor: Send + Sync {
+    /// Lowercase extensions without the dot
+    fn extensions(&self) -> &'static [&'static str];
+
+    fn mime_type(&self) -> &'static str;
+
+    fn extract(&self, bytes: &[u8]) -> Result<ExtractedContent, String>;
+}
+
+/// Extractors keyed by extension. The first registered match wins.
+pub struct ExtractorRegistry {
+    extractors: Vec<Box<dyn ContentExtractor>>,
+}
+
+impl ExtractorRegistry {
+    pub fn new() -> Self {
+        Self { extractors: Vec::new() }
+    }
+
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(MarkdownExtractor));
+        registry.register(Box::new(PlainTextExtractor));
+        registry.register(Box::new(OrgExtractor));
+        registry.register(Box::new(HtmlExtractor));
+        registry.register(Box::new(CsvExtractor));
+        registry.register(Box::new(JsonExtractor));
+        registry.register(Box::new(PdfExtractor));
+        registry
+    }
+
+    pub fn register(&mut self, extractor: Box<dyn ContentExtractor>) {
+        self.extractors.push(extractor);
+    }
+
+    pub fn for_path(&self, path: &Path) -> Option<&dyn ContentExtractor> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        self.extractors
+            .iter()
+            .find(|e| e.extensions().contains(&ext.as_str()))
+            .map(|e| e.as_ref())
+    }
+
+    pub fn supports(&self, path: &Path) -> bool {
+        self.for_path(path).is_some()
+    }
+}
+
+/// Shared registry with the built-in extractors.
+pub fn registry() -> &'static ExtractorRegistry {
+    static REGISTRY: OnceLock<ExtractorRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(ExtractorRegistry::with_defaults)
+}
+
+fn decode_text(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    text.strip_prefix('\u{feff}').unwrap_or(&text).to_string()
+}
+
+struct MarkdownExtractor;
+
+impl ContentExtractor for MarkdownExtractor {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["md", "markdown"]
+    }
+
+    fn mime_type(&self) -> &'static str {
+        MARKDOWN_MIME
+    }
+
+    fn extract(&self, bytes: &[u8]) -> Result<ExtractedContent, String> {
+        let text = decode_text(bytes);
+        let properties = frontmatter::parse(&text);
+        Ok(ExtractedContent {
+            title: frontmatter::title(&properties),
+            text,
+            properties,
+        })
+    }
+}
+
+struct PlainTextExtractor;
+
+impl ContentExtractor for PlainTextExtractor {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["txt", "text"]
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "text/plain"
+    }
+
+    fn extract(&self, bytes: &[u8]) -> Result<ExtractedContent, String> {
+        Ok(ExtractedContent {
+            title: None,
+            text: decode_text(bytes),
+            properties: Vec::new(),
+        })
+    }
+}
+
+struct OrgExtractor;
+
+impl ContentExtractor for OrgExtractor {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["org"]
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "text/org"
+    }
+
+    fn extract(&self, bytes: &[u8]) -> Result<ExtractedContent, String> {
+        let text = decode_text(bytes);
+
+        // `#+TITLE:` keywords are case-insensitive
+        let title = text.lines().find_map(|line| {
+            let line = line.trim();
+            let (keyword, value) = line.split_once(':')?;
+            if keyword.eq_ignore_ascii_case("#+title") && !value.trim().is_empty() {
+                Some(value.trim().to_string())
+            } else {
+                None
+            }
+        });
+
+        Ok(ExtractedContent {
+            title,
+            text,
+            properties: Vec::new(),
+        })
+    }
+}
+
+struct HtmlExtractor;
+
+impl ContentExtractor for HtmlExtractor {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["html", "htm"]
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "text/html"
+    }
+
+    fn extract(&self, bytes: &[u8]) -> Result<ExtractedContent, String> {
+        let html = decode_text(bytes);
+        // ASCII-only so byte offsets line up with the original
+        let lower = html.to_ascii_lowercase();
+
+        let title = lower.find("<title").and_then(|start| {
+            let open_end = start + lower[start..].find('>')? + 1;
+            let close = open_end + lower[open_end..].find("</title")?;
+            let title = decode_entities(html[open_end..close].trim());
+            (!title.is_empty()).then_some(title)
+        });
+
+        Ok(ExtractedContent {
+            title,
+            text: html_to_text(&html),
+            properties: Vec::new(),
+        })
+    }
+}
+
+/// Drops tags along with script and style bodies, keeping text content.
+fn html_to_text(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut text = String::with_capacity(html.len() / 2);
+    let mut pos = 0;
+
+    while let Some(offset) = html[pos..].find('<') {
+        let tag_start = pos + offset;
+        text.push_str(&html[pos..tag_start]);
+
+        let Some(tag_len) = html[tag_start..].find('>') else {
+            pos = html.len();
+            break;
+        };
+        let tag_end = tag_start + tag_len + 1;
+        pos = tag_end;
+
+        // Skip straight past the matching close tag for raw text elements
+        for raw in ["script", "style"] {
+            if lower[tag_start + 1..].starts_with(raw) {
+                let close = format!("</{}", raw);
+                pos = lower[tag_end..]
+                    .find(&close)
+                    .map(|i| tag_end + i)
+                    .unwrap_or(html.len());
+            }
+        }
+
+        text.push(' ');
+    }
+    text.push_str(&html[pos..]);
+
+    let decoded = decode_entities(&text);
+    decoded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        // Last, so "&amp;lt;" doesn't turn into "<"
+        .replace("&amp;", "&")
+}
+
+struct CsvExtractor;
+
+impl ContentExtractor for CsvExtractor {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["csv", "tsv"]
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "text/csv"
+    }
+
+    fn extract(&self, bytes: &[u8]) -> Result<ExtractedContent, String> {
+        let raw = decode_text(bytes);
+        let delimiter = if raw.lines().next().is_some_and(|l| l.contains('\t')) {
+            '\t'
+        } else {
+            ','
+        };
+
+        // One line of space-separated cell values per record
+        let text = raw
+            .lines()
+            .map(|line| split_record(line, delimiter).join(" "))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(ExtractedContent {
+            title: None,
+            text,
+            properties: Vec::new(),
+        })
+    }
+}
+
+fn split_record(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+        .into_iter()
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty())
+        .collect()
+}
+
+struct JsonExtractor;
+
+impl ContentExtractor for JsonExtractor {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn extract(&self, bytes: &[u8]) -> Result<ExtractedContent, String> {
+        let value: serde_json::Value =
+            serde_json::from_slice(bytes).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+        let title = ["title", "name"]
+            .iter()
+            .find_map(|key| value.get(key).and_then(|v| v.as_str()))
+            .map(|s| s.to_string());
+
+        let mut parts = Vec::new();
+        collect_json_text(&value, &mut parts);
+
+        Ok(ExtractedContent {
+            title,
+            text: parts.join("\n"),
+            properties: Vec::new(),
+        })
+    }
+}
+
+/// Keys and scalar values, depth first; structure isn't searchable anyway.
+fn collect_json_text(value: &serde_json::Value, parts: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                parts.push(key.clone());
+                collect_json_text(value, parts);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_json_text(item, parts);
+            }
+        }
+        serde_json::Value::String(s) => parts.push(s.clone()),
+        serde_json::Value::Number(n) => parts.push(n.to_string()),
+        serde_json::Value::Bool(_) | serde_json::Value::Null => {}
+    }
+}
+
+struct PdfExtractor;
+
+impl ContentExtractor for PdfExtractor {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["pdf"]
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/pdf"
+    }
+
+    fn extract(&self, bytes: &[u8]) -> Result<ExtractedContent, String> {
+        // The parser panics on some malformed and encrypted files
+        let text = panic::catch_unwind(AssertUnwindSafe(|| pdf_extract::extract_text_from_mem(bytes)))
+            .map_err(|_| "Failed to extract PDF text".to_string())?
+            .map_err(|e| format!("Failed to extract PDF text: {}", e))?;
+
+        Ok(ExtractedContent {
+            title: None,
+            text,
+            properties: Vec::new(),
+        })
+    }
+}
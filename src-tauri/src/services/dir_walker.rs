@@ -0,0 +1,193 @@
+use crate::models::index::SkippedEntry;
+use crate::services::ignore_rules::IgnoreRules;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_MAX_DEPTH: usize = 32;
+
+/// Per-root traversal policy.
+#[derive(Debug, Clone, Copy)]
+pub struct TraversalOptions {
+    /// Follow symlinks that stay inside the root; never follow ones that leave it
+    pub follow_symlinks: bool,
+    /// Directory levels below the root that are still walked
+    pub max_depth: usize,
+}
+
+impl Default for TraversalOptions {
+    fn default() -> Self {
+        Self {
+            follow_symlinks: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+}
+
+/// Files found by a walk plus everything that was deliberately not entered.
+#[derive(Debug, Default)]
+pub struct WalkResult {
+    pub files: Vec<PathBuf>,
+    pub skipped: Vec<SkippedEntry>,
+    /// Folders that couldn't be listed; what they held before is unknown, not gone
+    pub unreadable: Vec<PathBuf>,
+}
+
+/// Identifies a directory independently of the path it was reached by.
+#[cfg(unix)]
+type DirKey = (u64, u64);
+#[cfg(not(unix))]
+type DirKey = PathBuf;
+
+#[cfg(unix)]
+fn dir_key(path: &Path) -> Option<DirKey> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|m| (m.dev(), m.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_key(path: &Path) -> Option<DirKey> {
+    path.canonicalize().ok()
+}
+
+struct Walker<'a, F: Fn(&Path) -> bool> {
+    canonical_root: PathBuf,
+    options: &'a TraversalOptions,
+    include: F,
+    visited: HashSet<DirKey>,
+    result: WalkResult,
+}
+
+/// Walks `dir`, which is `root` itself or a folder inside it, collecting
+/// files accepted by `include`. Folders inside the root are walked the way a
+/// walk of the whole root would reach them: with the ignore files of every
+/// folder from `root` down, and with depth counted from `root`.
+///
+/// Dot-directories and paths matched by ignore rules are left out; symlinks,
+/// cycles, overly deep and unreadable folders are reported in `skipped`.
+/// Only an unreadable root fails the walk.
+pub fn walk_within(
+    root: &Path,
+    dir: &Path,
+    options: &TraversalOptions,
+    include: impl Fn(&Path) -> bool,
+) -> Result<WalkResult, String> {
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve directory: {}", e))?;
+
+    let mut walker = Walker {
+        canonical_root,
+        options,
+        include,
+        visited: HashSet::new(),
+        result: WalkResult::default(),
+    };
+
+    let depth = dir.strip_prefix(root).map_or(0, |relative| relative.components().count());
+    if depth > 0 {
+        // Checked here as they would have been on the way down from the root
+        if IgnoreRules::is_ignored_under(root, dir) {
+            return Ok(walker.result);
+        }
+        let is_symlink = fs::symlink_metadata(dir).is_ok_and(|m| m.file_type().is_symlink());
+        if is_symlink && !options.follow_symlinks {
+            walker.skip(dir, "symlink");
+            return Ok(walker.result);
+        }
+        if !dir.canonicalize().is_ok_and(|target| target.starts_with(&walker.canonical_root)) {
+            walker.skip(dir, "symlink outside root");
+            return Ok(walker.result);
+        }
+        if depth > options.max_depth {
+            walker.skip(dir, "max depth reached");
+            return Ok(walker.result);
+        }
+    }
+
+    if let Some(key) = dir_key(dir) {
+        walker.visited.insert(key);
+    }
+    walker.walk_dir(dir, &IgnoreRules::between(root, dir), depth)?;
+
+    Ok(walker.result)
+}
+
+impl<F: Fn(&Path) -> bool> Walker<'_, F> {
+    fn walk_dir(&mut self, dir: &Path, rules: &IgnoreRules, depth: usize) -> Result<(), String> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            // e.g. a permission-protected folder on macOS; the rest of the root is still walked
+            Err(_) if depth > 0 => {
+                self.skip(dir, "unreadable");
+                self.result.unreadable.push(dir.to_path_buf());
+                return Ok(());
+            }
+            Err(e) => return Err(format!("Failed to read directory: {}", e)),
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            let is_dir = if file_type.is_symlink() {
+                if !self.options.follow_symlinks {
+                    self.skip(&path, "symlink");
+                    continue;
+                }
+                match path.canonicalize() {
+                    Ok(target) if target.starts_with(&self.canonical_root) => target.is_dir(),
+                    Ok(_) => {
+                        self.skip(&path, "symlink outside root");
+                        continue;
+                    }
+                    Err(_) => {
+                        self.skip(&path, "broken symlink");
+                        continue;
+                    }
+                }
+            } else {
+                file_type.is_dir()
+            };
+
+            if rules.is_ignored(&path, is_dir) {
+                continue;
+            }
+
+            if is_dir {
+                // Skip hidden directories
+                if path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+                {
+                    continue;
+                }
+                if depth + 1 > self.options.max_depth {
+                    self.skip(&path, "max depth reached");
+                    continue;
+                }
+                // Already entered through another path: a cycle or a duplicate link
+                if let Some(key) = dir_key(&path) {
+                    if !self.visited.insert(key) {
+                        self.skip(&path, "already visited");
+                        continue;
+                    }
+                }
+                self.walk_dir(&path, &rules.descend(&path), depth + 1)?;
+            } else if (self.include)(&path) {
+                self.result.files.push(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn skip(&mut self, path: &Path, reason: &str) {
+        self.result.skipped.push(SkippedEntry {
+            path: path.to_string_lossy().to_string(),
+            reason: reason.to_string(),
+        });
+    }
+}
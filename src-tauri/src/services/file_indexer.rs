@@ -0,0 +1,404 @@
+use crate::models::note::Property;
+use crate::services::content_extractor::{registry, MARKDOWN_MIME};
+use crate::services::note_parser::{self, ParsedNote};
+use crate::services::{aliases, anchors, link_resolver, links, properties, tags, tasks};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Large PDFs and data dumps cost more to extract than they are worth in search
+const MAX_EXTRACT_SIZE: u64 = 25 * 1024 * 1024; // 25MB
+
+/// Result of indexing a single file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexOutcome {
+    Added,
+    Updated,
+    Unchanged,
+}
+
+/// What the index already knows about a file, used to skip unchanged ones.
+#[derive(Debug, Clone)]
+pub struct StoredFile {
+    pub id: i64,
+    /// Full-precision mtime and size; an edit in the same second as the last
+    /// index still changes one of the pair on any filesystem with sub-second
+    /// timestamps
+    pub mtime_ns: Option<i64>,
+    pub size: Option<i64>,
+    pub content_hash: Option<String>,
+}
+
+/// A file that has been read and extracted, ready to be written.
+pub struct PreparedDocument {
+    pub path: String,
+    pub title: Option<String>,
+    pub content: String,
+    pub modified: i64,
+    pub created: i64,
+    pub mtime_ns: i64,
+    pub size: i64,
+    pub content_hash: String,
+    pub mime_type: &'static str,
+    pub properties: Vec<Property>,
+    pub note: ParsedNote,
+    pub existing_id: Option<i64>,
+}
+
+/// Work left for the writer after a file has been examined.
+pub enum PreparedFile {
+    Unchanged,
+    /// Content is identical but the mtime moved (sync tools, git checkout)
+    Touched {
+        id: i64,
+        modified: i64,
+        mtime_ns: i64,
+        size: i64,
+    },
+    Document(Box<PreparedDocument>),
+}
+
+/// Indexes one file if an extractor handles its type. Unchanged files are
+/// detected by mtime and size, then by content hash, and left alone.
+pub fn index_file(conn: &Connection, path: &Path) -> Result<IndexOutcome, String> {
+    index_file_with(conn, path, false)
+}
+
+/// Like `index_file`, but re-extracts even if the file looks unchanged, for
+/// files just written by the app itself.
+pub fn reindex_file(conn: &Connection, path: &Path) -> Result<IndexOutcome, String> {
+    index_file_with(conn, path, true)
+}
+
+fn index_file_with(conn: &Connection, path: &Path, force: bool) -> Result<IndexOutcome, String> {
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| "Invalid file path".to_string())?;
+
+    let mut stored = lookup_stored(conn, path_str)?;
+    if force {
+        if let Some(stored) = stored.as_mut() {
+            stored.mtime_ns = None;
+            stored.content_hash = None;
+        }
+    }
+    let prepared = prepare_file(path, stored.as_ref())?;
+
+    // Use transaction for atomicity of file + FTS index update
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let outcome = write_prepared(&tx, prepared)?;
+
+    // Commit transaction (ensures atomicity)
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(outcome)
+}
+
+fn lookup_stored(conn: &Connection, path: &str) -> Result<Option<StoredFile>, String> {
+    conn.query_row(
+        "SELECT id, mtime_ns, size, content_hash FROM files WHERE path = ?1",
+        params![path],
+        |row| {
+            Ok(StoredFile {
+                id: row.get(0)?,
+                mtime_ns: row.get(1)?,
+                size: row.get(2)?,
+                content_hash: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Failed to look up file: {}", e))
+}
+
+/// Loads the stored state of every file under `directory` in one query, so
+/// crawl workers never have to touch the database.
+pub fn load_stored_under(conn: &Connection, directory: &str) -> Result<HashMap<PathBuf, StoredFile>, String> {
+    let prefix = format!("{}{}", directory.trim_end_matches(std::path::MAIN_SEPARATOR), std::path::MAIN_SEPARATOR);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT path, id, mtime_ns, size, content_hash FROM files
+             WHERE substr(path, 1, length(?1)) = ?1",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let stored = stmt
+        .query_map(params![prefix], |row| {
+            Ok((
+                PathBuf::from(row.get::<_, String>(0)?),
+                StoredFile {
+                    id: row.get(1)?,
+                    mtime_ns: row.get(2)?,
+                    size: row.get(3)?,
+                    content_hash: row.get(4)?,
+                },
+            ))
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(stored)
+}
+
+/// Reads, hashes and extracts a file. Does no database access, so it can run
+/// on any thread.
+pub fn prepare_file(path: &Path, stored: Option<&StoredFile>) -> Result<PreparedFile, String> {
+    let extractor = registry()
+        .for_path(path)
+        .ok_or_else(|| format!("Unsupported file type: {:?}", path))?;
+
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| "Invalid file path".to_string())?;
+
+    let metadata = fs::metadata(path).map_err(|e| format!("Failed to get metadata: {}", e))?;
+
+    if metadata.len() > MAX_EXTRACT_SIZE {
+        return Err(format!("File too large to index ({} bytes)", metadata.len()));
+    }
+
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok());
+    let modified = mtime.map(|d| d.as_secs() as i64).unwrap_or(0);
+    let mtime_ns = mtime.map(|d| d.as_nanos() as i64).unwrap_or(0);
+    let size = metadata.len() as i64;
+
+    let created = metadata
+        .created()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    // Same mtime and size and a stored hash: trust it without reading the file
+    if let Some(StoredFile { mtime_ns: Some(stored_mtime), size: Some(stored_size), content_hash: Some(_), .. }) = stored {
+        if *stored_mtime == mtime_ns && *stored_size == size {
+            return Ok(PreparedFile::Unchanged);
+        }
+    }
+
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let content_hash = hash_content(&bytes);
+
+    if let Some(StoredFile { id, content_hash: Some(stored_hash), .. }) = stored {
+        if *stored_hash == content_hash {
+            return Ok(PreparedFile::Touched { id: *id, modified, mtime_ns, size });
+        }
+    }
+
+    let extracted = extractor.extract(&bytes)?;
+    let title = extracted.title.or_else(|| {
+        path.file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string())
+    });
+
+    let note = if extractor.mime_type() == MARKDOWN_MIME {
+        note_parser::parse(&extracted.text, &extracted.properties)
+    } else {
+        ParsedNote::default()
+    };
+
+    Ok(PreparedFile::Document(Box::new(PreparedDocument {
+        path: path_str.to_string(),
+        title,
+        content: extracted.text,
+        modified,
+        created,
+        mtime_ns,
+        size,
+        content_hash,
+        mime_type: extractor.mime_type(),
+        properties: extracted.properties,
+        note,
+        existing_id: stored.map(|s| s.id),
+    })))
+}
+
+/// Writes a prepared file. Runs inside the caller's transaction so a batch
+/// of files can share one commit.
+pub fn write_prepared(conn: &Connection, prepared: PreparedFile) -> Result<IndexOutcome, String> {
+    let doc = match prepared {
+        PreparedFile::Unchanged => return Ok(IndexOutcome::Unchanged),
+        PreparedFile::Touched { id, modified, mtime_ns, size } => {
+            conn.execute(
+                "UPDATE files SET modified = ?1, mtime_ns = ?2, size = ?3 WHERE id = ?4",
+                params![modified, mtime_ns, size, id],
+            )
+            .map_err(|e| format!("Failed to update file: {}", e))?;
+            return Ok(IndexOutcome::Unchanged);
+        }
+        PreparedFile::Document(doc) => doc,
+    };
+
+    let (outcome, file_id) = match doc.existing_id {
+        Some(id) => {
+            // External content FTS tables need the old values to drop their tokens
+            conn.execute(
+                "INSERT INTO files_fts (files_fts, rowid, path, title, content)
+                 SELECT 'delete', id, path, title, content FROM files WHERE id = ?1",
+                params![id],
+            )
+            .map_err(|e| format!("Failed to update FTS index: {}", e))?;
+
+            // Update in place so the rowid shared with files_fts stays stable
+            conn.execute(
+                "UPDATE files SET title = ?1, content = ?2, modified = ?3, created = ?4,
+                     content_hash = ?5, mime_type = ?6, mtime_ns = ?7, size = ?8
+                 WHERE id = ?9",
+                params![
+                    doc.title,
+                    doc.content,
+                    doc.modified,
+                    doc.created,
+                    doc.content_hash,
+                    doc.mime_type,
+                    doc.mtime_ns,
+                    doc.size,
+                    id
+                ],
+            )
+            .map_err(|e| format!("Failed to update file: {}", e))?;
+
+            (IndexOutcome::Updated, id)
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO files (path, title, content, modified, created, content_hash, mime_type, mtime_ns, size)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    doc.path,
+                    doc.title,
+                    doc.content,
+                    doc.modified,
+                    doc.created,
+                    doc.content_hash,
+                    doc.mime_type,
+                    doc.mtime_ns,
+                    doc.size
+                ],
+            )
+            .map_err(|e| format!("Failed to insert file: {}", e))?;
+
+            let id = conn.last_insert_rowid();
+            links::mark_stale_names(conn, &link_resolver::file_keys(Path::new(&doc.path)))?;
+            (IndexOutcome::Added, id)
+        }
+    };
+
+    conn.execute(
+        "INSERT INTO files_fts (rowid, path, title, content)
+         SELECT id, path, title, content FROM files WHERE path = ?1",
+        params![doc.path],
+    )
+    .map_err(|e| format!("Failed to update FTS index: {}", e))?;
+
+    properties::replace(conn, file_id, &doc.properties)?;
+    aliases::replace(conn, file_id, &doc.note.aliases)?;
+    tags::replace(conn, file_id, &doc.note.tags)?;
+    links::replace(conn, file_id, &doc.note.links)?;
+    anchors::replace(conn, file_id, &doc.note.headings, &doc.note.blocks)?;
+    tasks::replace(conn, file_id, &doc.note.tasks)?;
+
+    Ok(outcome)
+}
+
+fn hash_content(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Removes a file, or every file under a directory, from the index.
+pub fn remove_path(conn: &Connection, path: &Path) -> Result<(), String> {
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| "Invalid file path".to_string())?;
+    let prefix = format!("{}{}", path_str.trim_end_matches(std::path::MAIN_SEPARATOR), std::path::MAIN_SEPARATOR);
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    // External content FTS tables need the old values to drop their tokens
+    tx.execute(
+        "INSERT INTO files_fts (files_fts, rowid, path, title, content)
+         SELECT 'delete', id, path, title, content FROM files
+         WHERE path = ?1 OR substr(path, 1, length(?2)) = ?2",
+        params![path_str, prefix],
+    )
+    .map_err(|e| format!("Failed to update FTS index: {}", e))?;
+
+    tx.execute(
+        "DELETE FROM files WHERE path = ?1 OR substr(path, 1, length(?2)) = ?2",
+        params![path_str, prefix],
+    )
+    .map_err(|e| format!("Failed to delete file: {}", e))?;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(())
+}
+
+/// Points the rows for `from` (a file, or every file under a folder) at
+/// their paths under `to`, keeping ids so links into them stay resolved.
+pub fn move_path(conn: &Connection, from: &Path, to: &Path) -> Result<Vec<(i64, String, String)>, String> {
+    let from_str = from
+        .to_str()
+        .ok_or_else(|| "Invalid file path".to_string())?;
+    let to_str = to.to_str().ok_or_else(|| "Invalid file path".to_string())?;
+    let prefix = format!("{}{}", from_str.trim_end_matches(std::path::MAIN_SEPARATOR), std::path::MAIN_SEPARATOR);
+
+    let moved: Vec<(i64, String, String)> = {
+        let mut stmt = conn
+            .prepare("SELECT id, path FROM files WHERE path = ?1 OR substr(path, 1, length(?2)) = ?2")
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        let rows = stmt
+            .query_map(params![from_str, prefix], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| format!("Failed to execute query: {}", e))?
+            .filter_map(|r| r.ok())
+            .map(|(id, old)| {
+                let new = format!("{}{}", to_str, &old[from_str.len()..]);
+                (id, old, new)
+            })
+            .collect();
+        rows
+    };
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    for (id, _, new) in &moved {
+        links::mark_stale_names(&tx, &link_resolver::file_keys(Path::new(new)))?;
+        // The path is indexed for search too, so the FTS row has to follow
+        tx.execute(
+            "INSERT INTO files_fts (files_fts, rowid, path, title, content)
+             SELECT 'delete', id, path, title, content FROM files WHERE id = ?1",
+            params![id],
+        )
+        .map_err(|e| format!("Failed to update FTS index: {}", e))?;
+        tx.execute("UPDATE files SET path = ?1 WHERE id = ?2", params![new, id])
+            .map_err(|e| format!("Failed to update file: {}", e))?;
+        tx.execute(
+            "INSERT INTO files_fts (rowid, path, title, content)
+             SELECT id, path, title, content FROM files WHERE id = ?1",
+            params![id],
+        )
+        .map_err(|e| format!("Failed to update FTS index: {}", e))?;
+    }
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(moved)
+}
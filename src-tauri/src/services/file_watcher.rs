@@ -0,0 +1,273 @@
+use crate::models::index::{FileChangeKind, FileChangedEvent};
+use crate::services::background_indexer::{index_directory_in_thread, CrawlControl};
+use crate::services::content_extractor::registry;
+use crate::services::file_indexer::{index_file, remove_path, IndexOutcome};
+use crate::services::ignore_rules::IgnoreRules;
+use crate::services::index_service::IndexService;
+use crate::services::links;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// Quiet period after the last event before a batch is applied
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(500);
+// Upper bound so a constant stream of events can't starve the index
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(3);
+
+/// Watches indexed roots and applies external edits to the search index.
+///
+/// The underlying OS watcher is created lazily on the first `watch` call,
+/// since it needs the app handle and database path.
+pub struct FileWatcher {
+    state: Mutex<Option<WatcherState>>,
+    roots: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+struct WatcherState {
+    watcher: RecommendedWatcher,
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(None),
+            roots: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    pub fn watch(&self, app_handle: AppHandle, db_path: String, root: &str) -> Result<(), String> {
+        let root_path = PathBuf::from(root);
+
+        if !self.roots.lock().unwrap().insert(root_path.clone()) {
+            return Ok(());
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if state.is_none() {
+            *state = Some(self.spawn(app_handle, db_path)?);
+        }
+
+        if let Some(state) = state.as_mut() {
+            if let Err(e) = state.watcher.watch(&root_path, RecursiveMode::Recursive) {
+                self.roots.lock().unwrap().remove(&root_path);
+                return Err(format!("Failed to watch {}: {}", root, e));
+            }
+        }
+
+        println!("Watching for changes: {}", root);
+        Ok(())
+    }
+
+    pub fn unwatch(&self, root: &str) {
+        let root_path = PathBuf::from(root);
+        if !self.roots.lock().unwrap().remove(&root_path) {
+            return;
+        }
+
+        if let Some(state) = self.state.lock().unwrap().as_mut() {
+            if let Err(e) = state.watcher.unwatch(&root_path) {
+                eprintln!("Failed to stop watching {}: {}", root, e);
+            }
+        }
+    }
+
+    fn spawn(&self, app_handle: AppHandle, db_path: String) -> Result<WatcherState, String> {
+        let (tx, rx) = mpsc::channel();
+
+        let watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+            Ok(event) => {
+                // Receiver only goes away when the debounce thread exits
+                let _ = tx.send(event);
+            }
+            Err(e) => eprintln!("File watcher error: {}", e),
+        })
+        .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+        let roots = self.roots.clone();
+        thread::spawn(move || debounce_loop(rx, roots, app_handle, db_path));
+
+        Ok(WatcherState { watcher })
+    }
+}
+
+/// Collects raw events into per-path changes and flushes them once the
+/// filesystem has been quiet for `DEBOUNCE_INTERVAL`.
+fn debounce_loop(
+    rx: Receiver<Event>,
+    roots: Arc<Mutex<HashSet<PathBuf>>>,
+    app_handle: AppHandle,
+    db_path: String,
+) {
+    let mut pending: HashMap<PathBuf, FileChangeKind> = HashMap::new();
+    let mut batch_started: Option<Instant> = None;
+
+    loop {
+        let timeout = match batch_started {
+            Some(started) => DEBOUNCE_INTERVAL.min(MAX_BATCH_DELAY.saturating_sub(started.elapsed())),
+            None => MAX_BATCH_DELAY,
+        };
+
+        match rx.recv_timeout(timeout) {
+            Ok(event) => {
+                if record_event(&mut pending, &event) {
+                    batch_started.get_or_insert_with(Instant::now);
+                }
+                let overdue = batch_started.is_some_and(|s| s.elapsed() >= MAX_BATCH_DELAY);
+                if !overdue {
+                    continue;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if !pending.is_empty() {
+            let batch = std::mem::take(&mut pending);
+            let roots = roots.lock().unwrap().clone();
+            apply_changes(&app_handle, &db_path, &roots, batch);
+        }
+        batch_started = None;
+    }
+}
+
+/// Returns true if the event contributed a change worth applying.
+fn record_event(pending: &mut HashMap<PathBuf, FileChangeKind>, event: &Event) -> bool {
+    let kind = match event.kind {
+        EventKind::Create(_) => FileChangeKind::Created,
+        EventKind::Remove(_) => FileChangeKind::Removed,
+        // The new name of a rename is a path appearing, like a create. Some
+        // platforms don't say which side a path is; existence is checked
+        // again at flush time either way
+        EventKind::Modify(ModifyKind::Name(RenameMode::To | RenameMode::Any)) => FileChangeKind::Created,
+        EventKind::Modify(_) => FileChangeKind::Modified,
+        EventKind::Access(_) | EventKind::Any | EventKind::Other => return false,
+    };
+
+    for (i, path) in event.paths.iter().enumerate() {
+        // `Both` lists the old name first and the new one second
+        let kind = match event.kind {
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if i > 0 => FileChangeKind::Created,
+            _ => kind,
+        };
+        pending
+            .entry(path.clone())
+            // A create followed by writes is still a create
+            .and_modify(|existing| {
+                if *existing != FileChangeKind::Created {
+                    *existing = kind;
+                }
+            })
+            .or_insert(kind);
+    }
+
+    !event.paths.is_empty()
+}
+
+fn apply_changes(
+    app_handle: &AppHandle,
+    db_path: &str,
+    roots: &HashSet<PathBuf>,
+    batch: HashMap<PathBuf, FileChangeKind>,
+) {
+    let service = IndexService::new(db_path.into());
+    let conn = match service.get_connection() {
+        Ok(conn) => conn,
+        Err(e) => {
+            eprintln!("File watcher failed to connect to database: {}", e);
+            return;
+        }
+    };
+
+    let mut changed = Vec::new();
+    for (path, kind) in batch {
+        if should_skip(&path, roots) {
+            continue;
+        }
+
+        let result = if path.is_dir() {
+            // Only a folder that appeared (created, or moved into the vault) is
+            // crawled; other folder events are metadata, and edits inside it
+            // arrive as events of their own
+            if kind != FileChangeKind::Created {
+                continue;
+            }
+            match (owning_root(&path, roots).and_then(|root| root.to_str()), path.to_str()) {
+                (Some(root), Some(dir)) => {
+                    index_directory_in_thread(db_path, root, dir, &CrawlControl::default()).map(|_| kind)
+                }
+                _ => Err("Invalid directory path".to_string()),
+            }
+        } else if path.exists() {
+            if !registry().supports(&path) {
+                continue;
+            }
+            match index_file(&conn, &path) {
+                // Touched but not edited; nothing for the frontend to refresh
+                Ok(IndexOutcome::Unchanged) => continue,
+                Ok(_) => Ok(kind),
+                Err(e) => Err(e),
+            }
+        } else {
+            // Covers deleted files, and folders by prefix
+            remove_path(&conn, &path).map(|_| FileChangeKind::Removed)
+        };
+
+        match result {
+            Ok(kind) => {
+                let event = FileChangedEvent {
+                    path: path.to_string_lossy().to_string(),
+                    kind,
+                };
+                changed.push(event.path.clone());
+                if let Err(e) = app_handle.emit("file-changed", event) {
+                    eprintln!("Failed to emit event: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to apply change for {:?}: {}", path, e),
+        }
+    }
+
+    // New, edited and deleted notes all change where links point
+    if let Err(e) = links::resolve_pending(&conn) {
+        eprintln!("Failed to resolve links: {}", e);
+    }
+
+    // Sent once the batch is fully indexed, so views built from queries can refresh
+    if !changed.is_empty() {
+        if let Err(e) = app_handle.emit("index-updated", changed) {
+            eprintln!("Failed to emit event: {}", e);
+        }
+    }
+}
+
+/// Skips paths outside every root, anything under a dot-directory and
+/// anything matched by ignore rules, mirroring the crawler.
+fn should_skip(path: &Path, roots: &HashSet<PathBuf>) -> bool {
+    let Some(root) = owning_root(path, roots) else {
+        return true;
+    };
+    let relative = path.strip_prefix(root).unwrap_or(path);
+
+    if relative
+        .components()
+        .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+    {
+        return true;
+    }
+
+    IgnoreRules::is_ignored_under(root, path)
+}
+
+/// The innermost watched root containing `path`.
+fn owning_root<'a>(path: &Path, roots: &'a HashSet<PathBuf>) -> Option<&'a PathBuf> {
+    roots
+        .iter()
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count())
+}
@@ -0,0 +1,96 @@
+use crate::models::note::{Property, PropertyType};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde_yaml::Value;
+
+/// The YAML block between a leading `---` line and the next `---` (or `...`)
+/// line, and the byte offset where the note body starts after it. `None` if
+/// the note doesn't start with frontmatter.
+pub fn split(text: &str) -> Option<(&str, usize)> {
+    let rest = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))?;
+    let start = text.len() - rest.len();
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return Some((&rest[..offset], start + offset + line.len()));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Typed properties of a note, in the order they appear. Invalid YAML
+/// yields no properties rather than failing the whole file.
+pub fn parse(text: &str) -> Vec<Property> {
+    let Some((yaml, _)) = split(text) else {
+        return Vec::new();
+    };
+    if yaml.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let mapping = match serde_yaml::from_str::<Value>(yaml) {
+        Ok(Value::Mapping(mapping)) => mapping,
+        Ok(_) => return Vec::new(),
+        Err(e) => {
+            eprintln!("Invalid frontmatter: {}", e);
+            return Vec::new();
+        }
+    };
+
+    mapping
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let key = match key {
+                Value::String(s) => s,
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => return None,
+            };
+            let (value_type, value) = typed_value(value);
+            Some(Property { key, value_type, value })
+        })
+        .collect()
+}
+
+/// `title` property if it is a non-empty string.
+pub fn title(properties: &[Property]) -> Option<String> {
+    properties
+        .iter()
+        .find(|p| p.key.eq_ignore_ascii_case("title"))
+        .and_then(|p| p.value.as_str())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn typed_value(value: Value) -> (PropertyType, serde_json::Value) {
+    match value {
+        Value::Tagged(tagged) => typed_value(tagged.value),
+        Value::Null => (PropertyType::String, serde_json::Value::Null),
+        Value::Bool(b) => (PropertyType::Bool, serde_json::Value::Bool(b)),
+        Value::Number(n) => (PropertyType::Number, to_json(&Value::Number(n))),
+        Value::String(s) if is_date(&s) => (PropertyType::Date, serde_json::Value::String(s)),
+        Value::String(s) => (PropertyType::String, serde_json::Value::String(s)),
+        value @ Value::Sequence(_) => (PropertyType::List, to_json(&value)),
+        value @ Value::Mapping(_) => (PropertyType::Object, to_json(&value)),
+    }
+}
+
+fn to_json(value: &Value) -> serde_json::Value {
+    // Only fails for maps with non-string keys, which have no JSON form
+    serde_json::to_value(value).unwrap_or(serde_json::Value::Null)
+}
+
+/// ISO 8601 dates and date-times, the forms YAML leaves as plain strings.
+pub fn is_date(value: &str) -> bool {
+    let value = value.trim();
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+        || NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").is_ok()
+        || NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M").is_ok()
+        || NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").is_ok()
+        || DateTime::parse_from_rfc3339(value).is_ok()
+}
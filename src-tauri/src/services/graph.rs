@@ -0,0 +1,300 @@
+use crate::models::graph::{Graph, GraphEdge, GraphFilter, GraphNode, GraphNodeKind};
+use crate::services::content_extractor::MARKDOWN_MIME;
+use crate::services::tags;
+use rusqlite::{params_from_iter, Connection};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, MAIN_SEPARATOR};
+
+/// Builds the note graph from the link and tag tables.
+pub fn build(conn: &Connection, filter: &GraphFilter) -> Result<Graph, String> {
+    let mut graph = GraphBuilder::default();
+
+    let notes = load_notes(conn, filter)?;
+    let mut ordered: Vec<&(String, Option<String>)> = notes.values().collect();
+    ordered.sort();
+    for (path, title) in ordered {
+        graph.add_node(path, GraphNodeKind::Note, title.clone().unwrap_or_else(|| file_label(path)));
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT l.source_id, l.kind, l.target, t.path, t.mime_type FROM links l
+             LEFT JOIN files t ON t.id = l.resolved_id",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    let links = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok());
+
+    for (source_id, kind, target, resolved_path, mime_type) in links {
+        let Some((source, _)) = notes.get(&source_id) else {
+            continue;
+        };
+
+        match resolved_path {
+            Some(path) if mime_type.as_deref().is_none_or(|m| m == MARKDOWN_MIME) => {
+                // Notes filtered out by folder or tag don't pull their neighbours in
+                if graph.contains(&path) && path != *source {
+                    graph.add_edge(source, &path, &kind);
+                }
+            }
+            Some(path) => {
+                if filter.include_attachments {
+                    graph.add_node(&path, GraphNodeKind::Attachment, file_label(&path));
+                    graph.add_edge(source, &path, &kind);
+                }
+            }
+            None => {
+                // Embedded images and other files the index doesn't store
+                if filter.include_attachments && kind == "embed" && Path::new(&target).extension().is_some() {
+                    let id = format!("attachment:{}", target);
+                    graph.add_node(&id, GraphNodeKind::Attachment, file_label(&target));
+                    graph.add_edge(source, &id, &kind);
+                }
+            }
+        }
+    }
+    drop(stmt);
+
+    if filter.include_tags {
+        let mut stmt = conn
+            .prepare("SELECT DISTINCT file_id, tag_key, tag FROM tags")
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })
+            .map_err(|e| format!("Failed to execute query: {}", e))?
+            .filter_map(|r| r.ok());
+
+        for (file_id, key, tag) in rows {
+            let Some((source, _)) = notes.get(&file_id) else {
+                continue;
+            };
+            let id = format!("tag:{}", key);
+            graph.add_node(&id, GraphNodeKind::Tag, format!("#{}", tag));
+            graph.add_edge(source, &id, "tag");
+        }
+    }
+
+    if let Some(center) = &filter.center {
+        graph.retain_neighbourhood(center, filter.depth.unwrap_or(1));
+    }
+
+    Ok(graph.finish())
+}
+
+/// Markdown notes passing the folder and tag filters, keyed by file id.
+fn load_notes(
+    conn: &Connection,
+    filter: &GraphFilter,
+) -> Result<HashMap<i64, (String, Option<String>)>, String> {
+    let mut sql = String::from(
+        "SELECT f.id, f.path, f.title FROM files f WHERE (f.mime_type IS NULL OR f.mime_type = ?1)",
+    );
+    let mut values = vec![MARKDOWN_MIME.to_string()];
+
+    if let Some(folder) = &filter.folder {
+        values.push(format!("{}{}", folder.trim_end_matches(MAIN_SEPARATOR), MAIN_SEPARATOR));
+        sql.push_str(&format!(
+            " AND substr(f.path, 1, length(?{0})) = ?{0}",
+            values.len()
+        ));
+    }
+    if let Some(tag) = &filter.tag {
+        values.push(tags::tag_key(tag));
+        sql.push_str(&format!(" AND {}", tags::filter_clause(values.len())));
+    }
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let notes = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            Ok((row.get::<_, i64>(0)?, (row.get(1)?, row.get(2)?)))
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(notes)
+}
+
+fn file_label(path: &str) -> String {
+    let path = Path::new(path);
+    path.file_stem()
+        .or_else(|| path.file_name())
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[derive(Default)]
+struct GraphBuilder {
+    nodes: Vec<(String, GraphNodeKind, String)>,
+    index: HashMap<String, usize>,
+    edges: Vec<(usize, usize, String)>,
+    seen_edges: HashSet<(usize, usize, String)>,
+}
+
+impl GraphBuilder {
+    fn contains(&self, id: &str) -> bool {
+        self.index.contains_key(id)
+    }
+
+    fn add_node(&mut self, id: &str, kind: GraphNodeKind, label: String) -> usize {
+        if let Some(&i) = self.index.get(id) {
+            return i;
+        }
+        self.nodes.push((id.to_string(), kind, label));
+        self.index.insert(id.to_string(), self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    /// Repeated links between the same pair collapse into one edge.
+    fn add_edge(&mut self, source: &str, target: &str, kind: &str) {
+        let (Some(&s), Some(&t)) = (self.index.get(source), self.index.get(target)) else {
+            return;
+        };
+        if self.seen_edges.insert((s, t, kind.to_string())) {
+            self.edges.push((s, t, kind.to_string()));
+        }
+    }
+
+    /// Keeps only nodes within `depth` hops of `center`, following edges in
+    /// either direction.
+    fn retain_neighbourhood(&mut self, center: &str, depth: usize) {
+        let mut keep = vec![false; self.nodes.len()];
+
+        if let Some(&start) = self.index.get(center) {
+            let adjacency = self.adjacency();
+            let mut queue = VecDeque::from([(start, 0)]);
+            keep[start] = true;
+
+            while let Some((node, distance)) = queue.pop_front() {
+                if distance == depth {
+                    continue;
+                }
+                for &next in &adjacency[node] {
+                    if !keep[next] {
+                        keep[next] = true;
+                        queue.push_back((next, distance + 1));
+                    }
+                }
+            }
+        }
+
+        let mut remap = vec![None; self.nodes.len()];
+        let mut nodes = Vec::new();
+        for (i, node) in std::mem::take(&mut self.nodes).into_iter().enumerate() {
+            if keep[i] {
+                remap[i] = Some(nodes.len());
+                nodes.push(node);
+            }
+        }
+
+        self.edges = std::mem::take(&mut self.edges)
+            .into_iter()
+            .filter_map(|(s, t, kind)| Some((remap[s]?, remap[t]?, kind)))
+            .collect();
+        self.index = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, (id, _, _))| (id.clone(), i))
+            .collect();
+        self.nodes = nodes;
+    }
+
+    fn adjacency(&self) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); self.nodes.len()];
+        for (s, t, _) in &self.edges {
+            adjacency[*s].push(*t);
+            adjacency[*t].push(*s);
+        }
+        adjacency
+    }
+
+    fn finish(self) -> Graph {
+        let mut degree = vec![0; self.nodes.len()];
+        let mut components = UnionFind::new(self.nodes.len());
+        for (s, t, _) in &self.edges {
+            degree[*s] += 1;
+            degree[*t] += 1;
+            components.union(*s, *t);
+        }
+
+        // Number components densely in order of first appearance
+        let mut component_ids = HashMap::new();
+        let nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, (id, kind, label))| {
+                let root = components.find(i);
+                let next = component_ids.len();
+                GraphNode {
+                    id: id.clone(),
+                    kind: *kind,
+                    label: label.clone(),
+                    degree: degree[i],
+                    component: *component_ids.entry(root).or_insert(next),
+                }
+            })
+            .collect();
+
+        let edges = self
+            .edges
+            .into_iter()
+            .map(|(s, t, kind)| GraphEdge {
+                source: self.nodes[s].0.clone(),
+                target: self.nodes[t].0.clone(),
+                kind,
+            })
+            .collect();
+
+        Graph { nodes, edges }
+    }
+}
+
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, node: usize) -> usize {
+        let mut root = node;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // Path compression
+        let mut node = node;
+        while self.parent[node] != root {
+            let next = self.parent[node];
+            self.parent[node] = root;
+            node = next;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent[b] = a;
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Vault-level ignore file, same syntax as `.gitignore`. Rules here win over
+/// `.gitignore` rules in the same directory.
+pub const VAULT_IGNORE_FILE: &str = ".personalosignore";
+
+const IGNORE_FILES: [&str; 2] = [".gitignore", VAULT_IGNORE_FILE];
+
+/// Gitignore-syntax rules in effect for one directory: the ignore files of
+/// that directory and of every ancestor down from the crawl root.
+#[derive(Clone, Default)]
+pub struct IgnoreRules {
+    // Shallowest first; deeper files take precedence
+    matchers: Vec<Arc<Gitignore>>,
+}
+
+impl IgnoreRules {
+    pub fn for_root(root: &Path) -> Self {
+        Self::default().descend(root)
+    }
+
+    /// Rules for `dir` when the crawl root isn't known, as in the file tree.
+    /// Ignore files are picked up from the nearest enclosing git repository or
+    /// vault (a folder with `.git` or a vault ignore file) down to `dir`.
+    pub fn for_directory(dir: &Path) -> Self {
+        let top = dir
+            .ancestors()
+            .find(|a| a.join(".git").exists() || a.join(VAULT_IGNORE_FILE).exists())
+            .unwrap_or(dir);
+
+        Self::between(top, dir)
+    }
+
+    /// Checks `path` and each of its ancestors below `root`, so files inside an
+    /// ignored folder are caught without walking the tree.
+    pub fn is_ignored_under(root: &Path, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return false;
+        };
+
+        let mut rules = Self::for_root(root);
+        let mut current = root.to_path_buf();
+        for component in relative.components() {
+            current.push(component);
+            let is_dir = current != path || path.is_dir();
+
+            if rules.is_ignored(&current, is_dir) {
+                return true;
+            }
+            if is_dir {
+                rules = rules.descend(&current);
+            }
+        }
+
+        false
+    }
+
+    /// Rules for `dir` inside the crawl root `root`: the ignore files of the
+    /// root and of every folder down to `dir`.
+    pub fn between(root: &Path, dir: &Path) -> Self {
+        let mut rules = Self::for_root(root);
+
+        if let Ok(relative) = dir.strip_prefix(root) {
+            let mut current = root.to_path_buf();
+            for component in relative.components() {
+                current.push(component);
+                rules = rules.descend(&current);
+            }
+        }
+
+        rules
+    }
+
+    /// Rules for a subdirectory, adding its own ignore files if it has any.
+    pub fn descend(&self, dir: &Path) -> Self {
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut found = false;
+
+        for name in IGNORE_FILES {
+            let file = dir.join(name);
+            if file.is_file() {
+                found = true;
+                if let Some(e) = builder.add(&file) {
+                    eprintln!("Invalid ignore rules in {:?}: {}", file, e);
+                }
+            }
+        }
+
+        let mut rules = self.clone();
+        if found {
+            match builder.build() {
+                Ok(gitignore) if !gitignore.is_empty() => rules.matchers.push(Arc::new(gitignore)),
+                Ok(_) => {}
+                Err(e) => eprintln!("Failed to load ignore rules in {:?}: {}", dir, e),
+            }
+        }
+
+        rules
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for matcher in self.matchers.iter().rev() {
+            match matcher.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+
+        false
+    }
+}
@@ -0,0 +1,104 @@
+use crate::models::index::{IndexCheckReport, IndexRepairReport};
+use rusqlite::{params, Connection};
+use std::path::{Path, MAIN_SEPARATOR};
+
+/// Compares `files` with `files_fts` and with the filesystem.
+pub fn check(conn: &Connection) -> Result<IndexCheckReport, String> {
+    // rank = 1 also verifies the index against the external content table
+    let fts_error = conn
+        .execute(
+            "INSERT INTO files_fts (files_fts, rank) VALUES ('integrity-check', 1)",
+            [],
+        )
+        .err()
+        .map(|e| e.to_string());
+
+    let file_rows: i64 = conn
+        .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to count files: {}", e))?;
+
+    // Selecting from files_fts itself would read the content table instead
+    let fts_rows: i64 = conn
+        .query_row("SELECT COUNT(*) FROM files_fts_docsize", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to count indexed documents: {}", e))?;
+
+    let roots = known_roots(conn)?;
+    let mut missing_files = Vec::new();
+    let mut outside_roots = Vec::new();
+
+    for path in indexed_paths(conn)? {
+        if !Path::new(&path).exists() {
+            missing_files.push(path);
+        } else if !roots.is_empty() && !roots.iter().any(|prefix| path.starts_with(prefix.as_str())) {
+            outside_roots.push(path);
+        }
+    }
+
+    Ok(IndexCheckReport {
+        fts_ok: fts_error.is_none() && file_rows == fts_rows,
+        fts_error,
+        file_rows,
+        fts_rows,
+        missing_files,
+        outside_roots,
+    })
+}
+
+/// Drops rows for files that no longer exist and rebuilds the full-text
+/// index from `files`. Files outside the known roots are only reported.
+pub fn repair(conn: &Connection) -> Result<IndexRepairReport, String> {
+    let before = check(conn)?;
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    // No per-row FTS delete: the index may be what's broken, and the rebuild
+    // below regenerates it from `files` anyway
+    for path in &before.missing_files {
+        tx.execute("DELETE FROM files WHERE path = ?1", params![path])
+            .map_err(|e| format!("Failed to delete file: {}", e))?;
+    }
+
+    tx.execute("INSERT INTO files_fts (files_fts) VALUES ('rebuild')", [])
+        .map_err(|e| format!("Failed to rebuild FTS index: {}", e))?;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(IndexRepairReport {
+        removed_missing: before.missing_files.len(),
+        fts_rebuilt: true,
+        after: check(conn)?,
+    })
+}
+
+fn indexed_paths(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT path FROM files ORDER BY path")
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let paths = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|p| p.ok())
+        .collect();
+
+    Ok(paths)
+}
+
+/// Separator-terminated prefixes of every finished or queued root.
+fn known_roots(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT path FROM roots UNION SELECT root FROM index_jobs")
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let roots = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok())
+        .map(|root| format!("{}{}", root.trim_end_matches(MAIN_SEPARATOR), MAIN_SEPARATOR))
+        .collect();
+
+    Ok(roots)
+}
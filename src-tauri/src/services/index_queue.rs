@@ -0,0 +1,147 @@
+use crate::models::index::IndexJob;
+use rusqlite::{params, Connection, OptionalExtension};
+
+// Directories the user just opened jump ahead of everything else
+pub const PRIORITY_HIGH: i64 = 20;
+pub const PRIORITY_NORMAL: i64 = 10;
+// Catch-up crawls of known roots at startup
+pub const PRIORITY_LOW: i64 = 0;
+
+const STATUS_PENDING: &str = "pending";
+const STATUS_RUNNING: &str = "running";
+
+/// Adds a root to the queue. A pending job for the same root is reused and
+/// keeps the higher of the two priorities (and `force` if either had it). A
+/// running one keeps going with the options it started with; a forced
+/// request (a rebuild, changed root options) then queues a rerun for when it
+/// finishes, otherwise it is dropped.
+pub fn enqueue(conn: &Connection, root: &str, priority: i64, force: bool) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO index_jobs (root, priority, status, enqueued_at, force)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(root) DO UPDATE SET
+             priority = MAX(priority, excluded.priority),
+             force = MAX(force, excluded.force),
+             rerun = status = ?6
+         WHERE status = ?3 OR excluded.force",
+        params![root, priority, STATUS_PENDING, chrono::Utc::now().timestamp(), force, STATUS_RUNNING],
+    )
+    .map_err(|e| format!("Failed to enqueue indexing job: {}", e))?;
+
+    Ok(())
+}
+
+/// Marks the highest priority pending job as running and returns it.
+pub fn claim_next(conn: &Connection) -> Result<Option<IndexJob>, String> {
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let root: Option<String> = tx
+        .query_row(
+            "SELECT root FROM index_jobs
+             WHERE status = ?1
+             ORDER BY priority DESC, enqueued_at ASC, id ASC
+             LIMIT 1",
+            params![STATUS_PENDING],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read indexing queue: {}", e))?;
+
+    let Some(root) = root else {
+        return Ok(None);
+    };
+
+    tx.execute(
+        "UPDATE index_jobs SET status = ?1, started_at = ?2 WHERE root = ?3",
+        params![STATUS_RUNNING, chrono::Utc::now().timestamp(), root],
+    )
+    .map_err(|e| format!("Failed to update indexing job: {}", e))?;
+
+    let job = tx
+        .query_row(
+            "SELECT root, priority, status, enqueued_at, started_at, force FROM index_jobs WHERE root = ?1",
+            params![root],
+            row_to_job,
+        )
+        .map_err(|e| format!("Failed to read indexing job: {}", e))?;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(Some(job))
+}
+
+/// Drops a job once its worker has finished, successfully or not, unless a
+/// rerun was requested while it ran and `rerun` allows it; then the job goes
+/// back in the queue. Returns true if it was queued again.
+pub fn finish(conn: &Connection, root: &str, rerun: bool) -> Result<bool, String> {
+    if rerun {
+        let requeued = conn
+            .execute(
+                "UPDATE index_jobs SET status = ?1, rerun = 0, started_at = NULL, enqueued_at = ?2
+                 WHERE root = ?3 AND rerun = 1",
+                params![STATUS_PENDING, chrono::Utc::now().timestamp(), root],
+            )
+            .map_err(|e| format!("Failed to requeue indexing job: {}", e))?;
+        if requeued > 0 {
+            return Ok(true);
+        }
+    }
+
+    conn.execute("DELETE FROM index_jobs WHERE root = ?1", params![root])
+        .map_err(|e| format!("Failed to remove indexing job: {}", e))?;
+
+    Ok(false)
+}
+
+/// Removes a job that has not started yet. Returns false if there was none.
+pub fn remove_pending(conn: &Connection, root: &str) -> Result<bool, String> {
+    let removed = conn
+        .execute(
+            "DELETE FROM index_jobs WHERE root = ?1 AND status = ?2",
+            params![root, STATUS_PENDING],
+        )
+        .map_err(|e| format!("Failed to remove indexing job: {}", e))?;
+
+    Ok(removed > 0)
+}
+
+/// Puts jobs that were running when the app last exited back in the queue.
+pub fn reset_interrupted(conn: &Connection) -> Result<usize, String> {
+    conn.execute(
+        "UPDATE index_jobs SET status = ?1, started_at = NULL, rerun = 0 WHERE status = ?2",
+        params![STATUS_PENDING, STATUS_RUNNING],
+    )
+    .map_err(|e| format!("Failed to reset indexing jobs: {}", e))
+}
+
+/// Running jobs first, then pending ones in the order they will be picked.
+pub fn list(conn: &Connection) -> Result<Vec<IndexJob>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT root, priority, status, enqueued_at, started_at, force FROM index_jobs
+             ORDER BY status = ?1 DESC, priority DESC, enqueued_at ASC, id ASC",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let jobs = stmt
+        .query_map(params![STATUS_RUNNING], row_to_job)
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|j| j.ok())
+        .collect();
+
+    Ok(jobs)
+}
+
+fn row_to_job(row: &rusqlite::Row) -> rusqlite::Result<IndexJob> {
+    Ok(IndexJob {
+        root: row.get(0)?,
+        priority: row.get(1)?,
+        status: row.get(2)?,
+        enqueued_at: row.get(3)?,
+        started_at: row.get(4)?,
+        force: row.get(5)?,
+    })
+}
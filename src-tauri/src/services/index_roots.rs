@@ -0,0 +1,169 @@
+use crate::models::index::IndexedRoot;
+use crate::services::dir_walker::TraversalOptions;
+use crate::services::file_indexer::remove_path;
+use rusqlite::OptionalExtension;
+use crate::utils::path::validate_path;
+use rusqlite::{params, Connection};
+use std::path::{Component, Path, PathBuf};
+
+/// Records a completed crawl of `root`, adding it if it is new.
+pub fn record_indexed(conn: &Connection, root: &str, file_count: usize) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+
+    conn.execute(
+        "INSERT INTO roots (path, added_at, last_indexed_at, file_count)
+         VALUES (?1, ?2, ?2, ?3)
+         ON CONFLICT(path) DO UPDATE SET
+             last_indexed_at = excluded.last_indexed_at,
+             file_count = excluded.file_count",
+        params![root, now, file_count as i64],
+    )
+    .map_err(|e| format!("Failed to record indexed root: {}", e))?;
+
+    Ok(())
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<IndexedRoot>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT path, added_at, last_indexed_at, file_count, follow_symlinks, max_depth
+             FROM roots ORDER BY path",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let roots = stmt
+        .query_map([], |row| {
+            Ok(IndexedRoot {
+                path: row.get(0)?,
+                added_at: row.get(1)?,
+                last_indexed_at: row.get(2)?,
+                file_count: row.get(3)?,
+                follow_symlinks: row.get(4)?,
+                max_depth: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(roots)
+}
+
+/// Traversal policy for crawling `root`; defaults if it isn't a known root.
+pub fn traversal_options(conn: &Connection, root: &str) -> Result<TraversalOptions, String> {
+    let stored: Option<(bool, Option<i64>)> = conn
+        .query_row(
+            "SELECT follow_symlinks, max_depth FROM roots WHERE path = ?1",
+            params![root],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load root options: {}", e))?;
+
+    let mut options = TraversalOptions::default();
+    if let Some((follow_symlinks, max_depth)) = stored {
+        options.follow_symlinks = follow_symlinks;
+        if let Some(depth) = max_depth {
+            options.max_depth = depth.max(0) as usize;
+        }
+    }
+
+    Ok(options)
+}
+
+/// Stores the traversal policy for `root`, adding the root if it is new.
+pub fn set_options(
+    conn: &Connection,
+    root: &str,
+    follow_symlinks: bool,
+    max_depth: Option<usize>,
+) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+
+    conn.execute(
+        "INSERT INTO roots (path, added_at, follow_symlinks, max_depth)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(path) DO UPDATE SET
+             follow_symlinks = excluded.follow_symlinks,
+             max_depth = excluded.max_depth",
+        params![root, now, follow_symlinks, max_depth.map(|d| d as i64)],
+    )
+    .map_err(|e| format!("Failed to save root options: {}", e))?;
+
+    Ok(())
+}
+
+/// Deletes every indexed file under `root`, its queued job and the root
+/// itself. Returns the number of files dropped from the index.
+pub fn forget(conn: &Connection, root: &str) -> Result<usize, String> {
+    let prefix = format!("{}{}", root.trim_end_matches(std::path::MAIN_SEPARATOR), std::path::MAIN_SEPARATOR);
+
+    let file_count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM files WHERE substr(path, 1, length(?1)) = ?1",
+            params![prefix],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count files: {}", e))?;
+
+    remove_path(conn, Path::new(root))?;
+
+    conn.execute("DELETE FROM index_jobs WHERE root = ?1", params![root])
+        .map_err(|e| format!("Failed to remove indexing job: {}", e))?;
+    conn.execute("DELETE FROM roots WHERE path = ?1", params![root])
+        .map_err(|e| format!("Failed to remove indexed root: {}", e))?;
+
+    Ok(file_count as usize)
+}
+
+/// `path` as given, once it exists and resolves to somewhere inside a known
+/// root. Commands check paths from the frontend this way before writing.
+pub fn existing_path_inside(conn: &Connection, path: &str) -> Result<PathBuf, String> {
+    let canonical = validate_path(path)?;
+    check_inside(conn, &canonical)?;
+    Ok(PathBuf::from(path))
+}
+
+/// Like `existing_path_inside`, for a path that is about to be created: it
+/// may not step out with `..`, and its nearest existing folder must be
+/// inside a known root.
+pub fn new_path_inside(conn: &Connection, path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err("Invalid path".to_string());
+    }
+    let existing = path
+        .ancestors()
+        .find(|a| a.exists())
+        .ok_or_else(|| "Path does not exist".to_string())?;
+    let canonical = validate_path(&existing.to_string_lossy())?;
+    check_inside(conn, &canonical)?;
+    Ok(path)
+}
+
+fn check_inside(conn: &Connection, canonical: &Path) -> Result<(), String> {
+    let inside = known_paths(conn)?
+        .iter()
+        .filter_map(|root| Path::new(root).canonicalize().ok())
+        .any(|root| canonical.starts_with(root));
+    if inside {
+        Ok(())
+    } else {
+        Err("Path is outside the indexed folders".to_string())
+    }
+}
+
+/// Every finished or queued root.
+fn known_paths(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT path FROM roots UNION SELECT root FROM index_jobs")
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let roots = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(roots)
+}
@@ -1,5 +1,15 @@
+use crate::services::content_extractor::MARKDOWN_MIME;
 use rusqlite::{Connection, Result};
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// Bumped whenever indexing starts deriving new data from notes. Older
+/// databases lose their content fingerprints so the next crawl re-extracts.
+const DERIVED_DATA_VERSION: i32 = 7;
+
+// How long a connection waits for another writer (a crawl batch, the watcher,
+// a command editing a note) before failing with "database is locked"
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub struct IndexService {
     db_path: PathBuf,
@@ -11,7 +21,7 @@ impl IndexService {
     }
 
     pub fn initialize(&self) -> Result<()> {
-        let conn = Connection::open(&self.db_path)?;
+        let conn = self.open()?;
 
         // Enable WAL mode for better concurrency (use pragma for setting)
         conn.pragma_update(None, "journal_mode", "WAL")?;
@@ -25,11 +35,20 @@ impl IndexService {
                 title TEXT,
                 content TEXT,
                 modified INTEGER,
-                created INTEGER
+                created INTEGER,
+                content_hash TEXT,
+                mime_type TEXT
             )",
             [],
         )?;
 
+        // Columns added after the first release; older databases are migrated in place
+        add_column_if_missing(&conn, "files", "content_hash", "TEXT")?;
+        add_column_if_missing(&conn, "files", "mime_type", "TEXT")?;
+        // Older rows have neither; their first crawl compares hashes and fills them in
+        add_column_if_missing(&conn, "files", "mtime_ns", "INTEGER")?;
+        add_column_if_missing(&conn, "files", "size", "INTEGER")?;
+
         // Create FTS5 virtual table for full-text search
         conn.execute(
             "CREATE VIRTUAL TABLE IF NOT EXISTS files_fts USING fts5(
@@ -40,13 +59,192 @@ impl IndexService {
             [],
         )?;
 
+        // Pending background indexing work, kept here so it survives restarts
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS index_jobs (
+                id INTEGER PRIMARY KEY,
+                root TEXT UNIQUE NOT NULL,
+                priority INTEGER NOT NULL DEFAULT 0,
+                status TEXT NOT NULL DEFAULT 'pending',
+                enqueued_at INTEGER NOT NULL,
+                started_at INTEGER,
+                force INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+        add_column_if_missing(&conn, "index_jobs", "force", "INTEGER NOT NULL DEFAULT 0")?;
+        // Set when a rebuild is requested while the job is running
+        add_column_if_missing(&conn, "index_jobs", "rerun", "INTEGER NOT NULL DEFAULT 0")?;
+
+        // Folders the index covers, so they are known across restarts
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS roots (
+                path TEXT PRIMARY KEY,
+                added_at INTEGER NOT NULL,
+                last_indexed_at INTEGER,
+                file_count INTEGER NOT NULL DEFAULT 0,
+                follow_symlinks INTEGER NOT NULL DEFAULT 0,
+                max_depth INTEGER
+            )",
+            [],
+        )?;
+        add_column_if_missing(&conn, "roots", "follow_symlinks", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(&conn, "roots", "max_depth", "INTEGER")?;
+
+        // Frontmatter of each note; `value` holds JSON
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS properties (
+                file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+                key TEXT NOT NULL,
+                value_type TEXT NOT NULL,
+                value TEXT,
+                position INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_properties_file ON properties(file_id)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_properties_key ON properties(key)", [])?;
+
+        // Frontmatter aliases; `alias_key` is the normalized form links are matched on
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS aliases (
+                file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+                alias TEXT NOT NULL,
+                alias_key TEXT NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_aliases_file ON aliases(file_id)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_aliases_key ON aliases(alias_key)", [])?;
+
+        // Inline and frontmatter tags; `tag_key` is the lowercased form used for matching
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tags (
+                file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+                tag TEXT NOT NULL,
+                tag_key TEXT NOT NULL,
+                line INTEGER
+            )",
+            [],
+        )?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_tags_file ON tags(file_id)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_tags_key ON tags(tag_key)", [])?;
+
+        // Outgoing links of each note; `resolved_id` is filled in after indexing
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS links (
+                id INTEGER PRIMARY KEY,
+                source_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+                target TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                line INTEGER NOT NULL,
+                context TEXT,
+                resolved_id INTEGER REFERENCES files(id) ON DELETE SET NULL
+            )",
+            [],
+        )?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_links_source ON links(source_id)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_links_resolved ON links(resolved_id)", [])?;
+
+        // Names that new or renamed files (or new aliases) answer to; links
+        // already resolved elsewhere are checked against them again
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS stale_link_names (
+                name TEXT PRIMARY KEY
+            )",
+            [],
+        )?;
+
+        // Link anchors: section headings and `^block-id` labels with their extents
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS headings (
+                file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+                level INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                text_key TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                start_byte INTEGER NOT NULL,
+                end_byte INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_headings_file ON headings(file_id)", [])?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS blocks (
+                file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+                block_id TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                start_byte INTEGER NOT NULL,
+                end_byte INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_blocks_file ON blocks(file_id)", [])?;
+
+        // Checklist items; dates are `YYYY-MM-DD` text so they sort and compare as strings
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS tasks (
+                file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+                line INTEGER NOT NULL,
+                status TEXT NOT NULL,
+                completed INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                heading TEXT,
+                due TEXT,
+                scheduled TEXT,
+                start TEXT,
+                created TEXT,
+                done TEXT,
+                priority TEXT,
+                recurrence TEXT
+            )",
+            [],
+        )?;
+        add_column_if_missing(&conn, "tasks", "recurrence", "TEXT")?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_tasks_file ON tasks(file_id)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_tasks_due ON tasks(due)", [])?;
+
+        let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version < DERIVED_DATA_VERSION {
+            conn.execute(
+                "UPDATE files SET content_hash = NULL WHERE mime_type IS NULL OR mime_type = ?1",
+                [MARKDOWN_MIME],
+            )?;
+            conn.pragma_update(None, "user_version", DERIVED_DATA_VERSION)?;
+        }
+
         Ok(())
     }
 
     pub fn get_connection(&self) -> Result<Connection> {
-        let conn = Connection::open(&self.db_path)?;
+        let conn = self.open()?;
         // WAL mode is already set at database level, no need to set per connection
+        // Foreign keys are per connection; derived tables rely on cascading deletes
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        Ok(conn)
+    }
+
+    fn open(&self) -> Result<Connection> {
+        let conn = Connection::open(&self.db_path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
         Ok(conn)
     }
 }
 
+/// `CREATE TABLE IF NOT EXISTS` won't touch an existing table, so new
+/// columns have to be added explicitly.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+
+    Ok(())
+}
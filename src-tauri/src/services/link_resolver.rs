@@ -0,0 +1,291 @@
+use crate::models::note::{LinkCandidate, LinkKind, WikilinkTarget};
+use crate::services::anchors;
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+struct Entry {
+    id: i64,
+    path: String,
+    /// Normalized, `/`-separated form of `path` for suffix matching
+    key: String,
+    /// Set for entries reached through a frontmatter alias
+    alias: Option<String>,
+}
+
+/// Every indexed path, keyed for link lookups. Resolution follows Obsidian:
+/// a bare name matches file names (the stem, for notes), a name with folders
+/// matches path suffixes, and ties go to the file nearest the linking note.
+/// Aliases only count when no file name matches.
+pub struct LinkIndex {
+    /// Normalized file name, plus the stem for markdown notes
+    by_name: HashMap<String, Vec<Entry>>,
+    by_alias: HashMap<String, Vec<Entry>>,
+    by_path: HashMap<String, i64>,
+}
+
+impl LinkIndex {
+    pub fn load(conn: &Connection) -> Result<Self, String> {
+        let mut stmt = conn
+            .prepare("SELECT id, path FROM files")
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| format!("Failed to execute query: {}", e))?
+            .filter_map(|r| r.ok());
+
+        let mut index = Self {
+            by_name: HashMap::new(),
+            by_alias: HashMap::new(),
+            by_path: HashMap::new(),
+        };
+        for (id, path) in rows {
+            for name in file_keys(Path::new(&path)) {
+                index.by_name.entry(name).or_default().push(Entry {
+                    id,
+                    path: path.clone(),
+                    key: normalize_key(&path.replace('\\', "/")),
+                    alias: None,
+                });
+            }
+            index.by_path.insert(path, id);
+        }
+        drop(stmt);
+
+        let mut stmt = conn
+            .prepare("SELECT a.file_id, f.path, a.alias, a.alias_key FROM aliases a JOIN files f ON f.id = a.file_id")
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to execute query: {}", e))?
+            .filter_map(|r| r.ok());
+
+        for (id, path, alias, alias_key) in rows {
+            index.by_alias.entry(alias_key).or_default().push(Entry {
+                id,
+                key: normalize_key(&path.replace('\\', "/")),
+                path,
+                alias: Some(alias),
+            });
+        }
+
+        Ok(index)
+    }
+
+    /// Resolves a link found in `source` to the id of the file it points at.
+    pub fn resolve(&self, source: &str, target: &str, kind: LinkKind) -> Option<i64> {
+        let (target, _) = anchors::split_link(target);
+        if target.is_empty() {
+            // `[[#Heading]]` points into the same note
+            return self.by_path.get(source).copied();
+        }
+
+        let current_dir = Path::new(source).parent()?;
+        match kind {
+            LinkKind::Markdown => self.resolve_relative(current_dir, target),
+            LinkKind::Wikilink | LinkKind::Embed => self
+                .candidates(current_dir, target)
+                .first()
+                .map(|c| c.id)
+                .or_else(|| self.resolve_relative(current_dir, target)),
+        }
+    }
+
+    /// Every file `target` could mean, best match first.
+    pub fn candidates(&self, current_dir: &Path, target: &str) -> Vec<Candidate> {
+        let (name, target) = target_keys(target);
+        let (name, target) = (name.as_str(), target.as_str());
+
+        let suffix = format!("/{}", target);
+        let mut candidates = self.ranked(current_dir, self.by_name.get(name), |entry| {
+            !target.contains('/')
+                || entry.key.ends_with(&suffix)
+                || strip_markdown_ext(&entry.key).ends_with(&suffix)
+        });
+
+        // Whole-alias matches rank below every file name match
+        let aliased = self.ranked(current_dir, self.by_alias.get(target), |entry| {
+            !candidates.iter().any(|c| c.id == entry.id)
+        });
+        candidates.extend(aliased);
+
+        candidates
+    }
+
+    fn ranked(
+        &self,
+        current_dir: &Path,
+        entries: Option<&Vec<Entry>>,
+        keep: impl Fn(&Entry) -> bool,
+    ) -> Vec<Candidate> {
+        let mut candidates: Vec<Candidate> = entries
+            .into_iter()
+            .flatten()
+            .filter(|entry| keep(entry))
+            .map(|entry| Candidate {
+                id: entry.id,
+                path: entry.path.clone(),
+                distance: distance(current_dir, Path::new(&entry.path)),
+                alias: entry.alias.clone(),
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| {
+            (a.distance, a.path.len(), &a.path).cmp(&(b.distance, b.path.len(), &b.path))
+        });
+        candidates
+    }
+
+    /// Paths relative to the linking note, as markdown links use them.
+    fn resolve_relative(&self, current_dir: &Path, target: &str) -> Option<i64> {
+        let joined = normalize_path(&current_dir.join(target));
+        let path = joined.to_str()?;
+
+        self.by_path
+            .get(path)
+            .or_else(|| self.by_path.get(&format!("{}.md", path)))
+            .copied()
+    }
+}
+
+pub struct Candidate {
+    pub id: i64,
+    pub path: String,
+    pub distance: usize,
+    /// The alias that matched, if it wasn't the file name
+    pub alias: Option<String>,
+}
+
+/// Resolves the text of a `[[link]]` typed in the note at `source`.
+/// `[[#Heading]]` points into that note itself.
+pub fn resolve_wikilink(
+    conn: &Connection,
+    link: &str,
+    source: &str,
+) -> Result<Option<WikilinkTarget>, String> {
+    let target = link.split('|').next().unwrap_or(link).trim();
+    let (_, anchor) = anchors::split_link(target);
+
+    let Some(best) = wikilink_candidates(conn, link, source)?.into_iter().next() else {
+        return Ok(None);
+    };
+    let anchor = match anchor {
+        Some(anchor) => anchors::find(conn, &best.path, anchor)?,
+        None => None,
+    };
+
+    Ok(Some(WikilinkTarget {
+        path: best.path,
+        anchor,
+    }))
+}
+
+/// All notes a link written in the note at `source` could resolve to, best
+/// match first, for letting the user pick when a name is ambiguous.
+pub fn wikilink_candidates(
+    conn: &Connection,
+    link: &str,
+    source: &str,
+) -> Result<Vec<LinkCandidate>, String> {
+    let target = link.split('|').next().unwrap_or(link).trim();
+    let (note, anchor) = anchors::split_link(target);
+
+    if note.is_empty() {
+        return Ok(anchor
+            .map(|_| LinkCandidate {
+                path: source.to_string(),
+                distance: 0,
+                alias: None,
+            })
+            .into_iter()
+            .collect());
+    }
+
+    let index = LinkIndex::load(conn)?;
+    let current_dir = Path::new(source).parent().unwrap_or(Path::new(""));
+    Ok(index
+        .candidates(current_dir, note)
+        .into_iter()
+        .map(|c| LinkCandidate {
+            path: c.path,
+            distance: c.distance,
+            alias: c.alias,
+        })
+        .collect())
+}
+
+/// The names a link can reach `file` by: its file name, plus the stem for
+/// markdown notes.
+pub fn file_keys(file: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Some(name) = file.file_name().and_then(|n| n.to_str()) {
+        names.push(normalize_key(name));
+    }
+    if is_markdown(file) {
+        if let Some(stem) = file.file_stem().and_then(|s| s.to_str()) {
+            names.push(normalize_key(stem));
+        }
+    }
+    names
+}
+
+/// What `candidates` looks a link target (without its anchor) up by: the
+/// last path segment among file names, and the whole target among aliases.
+pub fn target_keys(target: &str) -> (String, String) {
+    let target = normalize_key(&target.replace('\\', "/"));
+    let target = target.trim_start_matches("./").trim_start_matches('/');
+    let name = target.rsplit('/').next().unwrap_or(target);
+    (name.to_string(), target.to_string())
+}
+
+/// Case-insensitive and indifferent to how accented characters are composed
+/// (macOS file names are decomposed, typed text usually isn't).
+pub fn normalize_key(text: &str) -> String {
+    text.nfc().collect::<String>().to_lowercase()
+}
+
+/// Folder steps between `dir` and the folder holding `file`.
+fn distance(dir: &Path, file: &Path) -> usize {
+    let from: Vec<Component> = dir.components().collect();
+    let to: Vec<Component> = file.parent().map(|p| p.components().collect()).unwrap_or_default();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    (from.len() - common) + (to.len() - common)
+}
+
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"))
+}
+
+fn strip_markdown_ext(path: &str) -> &str {
+    path.strip_suffix(".md")
+        .or_else(|| path.strip_suffix(".markdown"))
+        .unwrap_or(path)
+}
+
+/// Resolves `.` and `..` without touching the filesystem.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
@@ -0,0 +1,144 @@
+use crate::models::note::{Backlink, LinkKind};
+use crate::services::anchors;
+use crate::services::link_resolver::{self, LinkIndex};
+use crate::services::note_parser::ParsedLink;
+use rusqlite::{params, Connection};
+use std::collections::HashSet;
+
+/// Replaces the outgoing links of a file. They start out unresolved and are
+/// picked up by the next `resolve_pending`.
+pub fn replace(conn: &Connection, file_id: i64, links: &[ParsedLink]) -> Result<(), String> {
+    conn.execute("DELETE FROM links WHERE source_id = ?1", params![file_id])
+        .map_err(|e| format!("Failed to clear links: {}", e))?;
+
+    let mut stmt = conn
+        .prepare_cached(
+            "INSERT INTO links (source_id, target, kind, line, context) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    for link in links {
+        stmt.execute(params![file_id, link.target, link.kind.as_str(), link.line as i64, link.context])
+            .map_err(|e| format!("Failed to store link: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Records names that files can now be linked by, after a file was added or
+/// renamed or gained an alias. A link resolved before may have a better
+/// target now: a nearer note, or a file where it matched an alias.
+pub fn mark_stale_names(conn: &Connection, names: &[String]) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare_cached("INSERT OR IGNORE INTO stale_link_names (name) VALUES (?1)")
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    for name in names {
+        stmt.execute(params![name])
+            .map_err(|e| format!("Failed to record link name: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Resolves links that point nowhere yet: new or edited links, and links
+/// whose target was deleted or hadn't been indexed when they were written.
+/// Links whose target matches a name from `mark_stale_names` are resolved
+/// again too. Returns the number of links that now resolve somewhere new.
+pub fn resolve_pending(conn: &Connection) -> Result<usize, String> {
+    let stale_names: HashSet<String> = {
+        let mut stmt = conn
+            .prepare("SELECT name FROM stale_link_names")
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| format!("Failed to execute query: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+
+    let mut pending: Vec<(i64, String, String, String, Option<i64>)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT l.id, f.path, l.target, l.kind, l.resolved_id FROM links l
+                 JOIN files f ON f.id = l.source_id
+                 WHERE l.resolved_id IS NULL OR ?1",
+            )
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+        let rows = stmt
+            .query_map(params![!stale_names.is_empty()], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+            })
+            .map_err(|e| format!("Failed to execute query: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+    pending.retain(|(_, _, target, _, resolved_id)| {
+        let (name, whole) = link_resolver::target_keys(anchors::split_link(target).0);
+        resolved_id.is_none() || stale_names.contains(&name) || stale_names.contains(&whole)
+    });
+    if pending.is_empty() && stale_names.is_empty() {
+        return Ok(0);
+    }
+
+    let index = LinkIndex::load(conn)?;
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let mut resolved = 0;
+
+    for (id, source, target, kind, resolved_id) in pending {
+        let Some(target_id) = index.resolve(&source, &target, LinkKind::parse(&kind)) else {
+            continue;
+        };
+        if Some(target_id) != resolved_id {
+            tx.execute(
+                "UPDATE links SET resolved_id = ?1 WHERE id = ?2",
+                params![target_id, id],
+            )
+            .map_err(|e| format!("Failed to resolve link: {}", e))?;
+            resolved += 1;
+        }
+    }
+
+    tx.execute("DELETE FROM stale_link_names", [])
+        .map_err(|e| format!("Failed to clear link names: {}", e))?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(resolved)
+}
+
+/// Links from other notes (and the note itself) that resolve to `path`.
+pub fn backlinks(conn: &Connection, path: &str) -> Result<Vec<Backlink>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.path, s.title, l.target, l.kind, l.line, l.context FROM links l
+             JOIN files t ON t.id = l.resolved_id
+             JOIN files s ON s.id = l.source_id
+             WHERE t.path = ?1
+             ORDER BY s.path, l.line",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let backlinks = stmt
+        .query_map(params![path], |row| {
+            Ok(Backlink {
+                source_path: row.get(0)?,
+                source_title: row.get(1)?,
+                target: row.get(2)?,
+                kind: LinkKind::parse(&row.get::<_, String>(3)?),
+                line: row.get(4)?,
+                context: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+            })
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|b| b.ok())
+        .collect();
+
+    Ok(backlinks)
+}
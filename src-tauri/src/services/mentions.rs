@@ -0,0 +1,216 @@
+use crate::models::file::ModifiedFile;
+use crate::models::note::UnlinkedMention;
+use crate::services::content_extractor::MARKDOWN_MIME;
+use crate::services::file_indexer::reindex_file;
+use crate::services::link_resolver::{normalize_key, LinkIndex};
+use crate::services::links;
+use crate::services::note_parser::{self, link_spans, strip_inline_code};
+use rusqlite::{params, Connection};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path};
+
+/// Places in other notes where the note at `path` is named (by file name,
+/// title or alias) in plain text rather than linked. The full-text index
+/// narrows the search to notes containing one of the names; each is then
+/// scanned from disk for whole-word occurrences outside links and code.
+pub fn unlinked_mentions(conn: &Connection, path: &str) -> Result<Vec<UnlinkedMention>, String> {
+    let names = names(conn, path)?;
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Each name as a phrase, so punctuation in titles can't break the query
+    let query = names
+        .iter()
+        .map(|name| format!("content : \"{}\"", name.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT f.path, f.title
+             FROM files_fts
+             JOIN files f ON files_fts.rowid = f.id
+             WHERE files_fts MATCH ?1 AND f.path != ?2
+               AND (f.mime_type IS NULL OR f.mime_type = ?3)
+             ORDER BY f.path",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let sources: Vec<(String, Option<String>)> = stmt
+        .query_map(params![query, path, MARKDOWN_MIME], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut mentions = Vec::new();
+    for (source_path, source_title) in sources {
+        let content = match fs::read_to_string(&source_path) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", source_path, e);
+                continue;
+            }
+        };
+
+        for line in note_parser::body_lines(&content) {
+            for (start, end) in occurrences(line.text, &names) {
+                mentions.push(UnlinkedMention {
+                    source_path: source_path.clone(),
+                    source_title: source_title.clone(),
+                    line: line.number,
+                    start,
+                    end,
+                    text: line.text[start..end].to_string(),
+                    context: line.text.trim().to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(mentions)
+}
+
+/// Turns the mention at bytes `start..end` of `line` in `source` into a
+/// wikilink to `target`, keeping the mention as the link's display text when
+/// it differs from the link. Fails if the text there no longer names `target`.
+pub fn link_mention(
+    conn: &Connection,
+    target: &str,
+    source: &Path,
+    line: usize,
+    start: usize,
+    end: usize,
+) -> Result<ModifiedFile, String> {
+    let names = names(conn, target)?;
+    let content = fs::read_to_string(source).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let body_line = note_parser::body_lines(&content)
+        .into_iter()
+        .find(|l| l.number == line)
+        .ok_or_else(|| "Mention is no longer in the note".to_string())?;
+    if !occurrences(body_line.text, &names).contains(&(start, end)) {
+        return Err("Mention is no longer in the note".to_string());
+    }
+
+    let source_dir = source.parent().unwrap_or(Path::new(""));
+    let link_target = shortest_link(&LinkIndex::load(conn)?, source_dir, target);
+    let mention = &body_line.text[start..end];
+    let link = if normalize_key(mention) == normalize_key(&link_target) {
+        format!("[[{}]]", mention)
+    } else {
+        format!("[[{}|{}]]", link_target, mention)
+    };
+
+    let mut rewritten = content.clone();
+    rewritten.replace_range(body_line.offset + start..body_line.offset + end, &link);
+    fs::write(source, &rewritten).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    // The edit is made; an index failure now shouldn't hide how to undo it
+    if let Err(e) = reindex_file(conn, source).and_then(|_| links::resolve_pending(conn)) {
+        eprintln!("Failed to index {:?}: {}", source, e);
+    }
+
+    Ok(ModifiedFile {
+        path: source.to_string_lossy().to_string(),
+        previous_content: content,
+    })
+}
+
+/// File stem, title and aliases of the note, normalized and deduplicated.
+fn names(conn: &Connection, path: &str) -> Result<Vec<String>, String> {
+    let title: Option<String> = conn
+        .query_row("SELECT title FROM files WHERE path = ?1", params![path], |row| row.get(0))
+        .map_err(|e| format!("Failed to look up note: {}", e))?;
+
+    let mut stmt = conn
+        .prepare("SELECT a.alias FROM aliases a JOIN files f ON f.id = a.file_id WHERE f.path = ?1")
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    let aliases: Vec<String> = stmt
+        .query_map(params![path], |row| row.get(0))
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string());
+
+    let mut seen = HashSet::new();
+    Ok(stem
+        .into_iter()
+        .chain(title)
+        .chain(aliases)
+        .map(|name| normalize_key(name.trim()))
+        .filter(|name| !name.is_empty() && seen.insert(name.clone()))
+        .collect())
+}
+
+/// Byte ranges in `line` where one of `names` appears as whole words,
+/// outside links, inline code and tags. Longer names win over names they
+/// contain.
+fn occurrences(line: &str, names: &[String]) -> Vec<(usize, usize)> {
+    let mut masked = strip_inline_code(line);
+    let spans = link_spans(&masked);
+    for span in spans {
+        masked.replace_range(span.start..span.end, &" ".repeat(span.end - span.start));
+    }
+
+    let mut names: Vec<(&str, usize)> = names.iter().map(|n| (n.as_str(), n.chars().count())).collect();
+    names.sort_by_key(|&(_, len)| std::cmp::Reverse(len));
+
+    let starts: Vec<usize> = masked.char_indices().map(|(i, _)| i).chain([masked.len()]).collect();
+    let mut found: Vec<(usize, usize)> = Vec::new();
+    for (name, len) in names {
+        for (n, &start) in starts.iter().enumerate() {
+            let Some(&end) = starts.get(n + len) else {
+                break;
+            };
+            if normalize_key(&masked[start..end]) != name
+                || !is_boundary(&masked[..start], true)
+                || !is_boundary(&masked[end..], false)
+                || found.iter().any(|&(s, e)| start < e && s < end)
+            {
+                continue;
+            }
+            found.push((start, end));
+        }
+    }
+
+    found.sort();
+    found
+}
+
+fn is_boundary(side: &str, before: bool) -> bool {
+    let next = if before { side.chars().next_back() } else { side.chars().next() };
+    match next {
+        None => true,
+        // `#name` is a tag, not a mention
+        Some('#') if before => false,
+        Some(c) => !(c.is_alphanumeric() || c == '_'),
+    }
+}
+
+/// The fewest trailing path segments of `target` that a wikilink from
+/// `source_dir` resolves to it with, without the `.md` extension.
+fn shortest_link(index: &LinkIndex, source_dir: &Path, target: &str) -> String {
+    let path = Path::new(target);
+    let stem = path.with_extension("");
+    let segments: Vec<String> = stem
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    for keep in 1..=segments.len() {
+        let text = segments[segments.len() - keep..].join("/");
+        let best = index.candidates(source_dir, &text).into_iter().next();
+        if best.is_some_and(|c| c.path == target) {
+            return text;
+        }
+    }
+    segments.join("/")
+}
@@ -1,3 +1,28 @@
 pub mod index_service;
+pub mod aliases;
+pub mod anchors;
 pub mod background_indexer;
-
+pub mod content_extractor;
+pub mod dir_walker;
+pub mod file_indexer;
+pub mod frontmatter;
+pub mod graph;
+pub mod index_integrity;
+pub mod index_queue;
+pub mod index_roots;
+pub mod file_watcher;
+pub mod ignore_rules;
+pub mod link_resolver;
+pub mod links;
+pub mod mentions;
+pub mod note_parser;
+pub mod properties;
+pub mod query;
+pub mod query_parser;
+pub mod rename;
+pub mod render;
+pub mod search;
+pub mod search_parser;
+pub mod tags;
+pub mod task_parser;
+pub mod tasks;
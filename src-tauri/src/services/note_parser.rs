@@ -0,0 +1,625 @@
+use crate::models::note::{LinkKind, Property, SourceRange};
+use crate::services::frontmatter;
+use crate::services::task_parser::{self, ParsedTask};
+
+/// Structured data pulled out of a markdown note for the derived tables.
+#[derive(Debug, Default)]
+pub struct ParsedNote {
+    /// Other names the note answers to in links and the quick switcher
+    pub aliases: Vec<String>,
+    pub tags: Vec<ParsedTag>,
+    pub links: Vec<ParsedLink>,
+    pub headings: Vec<ParsedHeading>,
+    pub blocks: Vec<ParsedBlock>,
+    pub tasks: Vec<ParsedTask>,
+}
+
+#[derive(Debug)]
+pub struct ParsedTag {
+    /// As written, without the `#`
+    pub tag: String,
+    /// `None` for tags from frontmatter
+    pub line: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct ParsedLink {
+    /// Link target as written, including any `#heading` part but not the alias
+    pub target: String,
+    pub kind: LinkKind,
+    pub line: usize,
+    /// The surrounding line, for backlink previews
+    pub context: String,
+}
+
+#[derive(Debug)]
+pub struct ParsedHeading {
+    pub level: usize,
+    pub text: String,
+    /// From the heading line to just before the next heading of the same or
+    /// a higher level
+    pub range: SourceRange,
+}
+
+/// A `^block-id` anchor and the paragraph or list item it labels.
+#[derive(Debug)]
+pub struct ParsedBlock {
+    pub id: String,
+    pub range: SourceRange,
+}
+
+/// A line of the note body: frontmatter and fenced code blocks are left out.
+pub struct BodyLine<'a> {
+    /// 1-based, counted from the top of the file
+    pub number: usize,
+    /// Byte offset of the line start in the file
+    pub offset: usize,
+    pub text: &'a str,
+}
+
+pub fn parse(text: &str, properties: &[Property]) -> ParsedNote {
+    let mut note = ParsedNote::default();
+
+    for property in properties {
+        if property.key.eq_ignore_ascii_case("tags") || property.key.eq_ignore_ascii_case("tag") {
+            for tag in frontmatter_tags(&property.value) {
+                note.tags.push(ParsedTag { tag, line: None });
+            }
+        }
+        if property.key.eq_ignore_ascii_case("aliases") || property.key.eq_ignore_ascii_case("alias") {
+            note.aliases.extend(frontmatter_aliases(&property.value));
+        }
+    }
+
+    let lines = body_lines(text);
+    note.headings = headings(text, &lines);
+    note.blocks = blocks(&lines);
+    note.tasks = tasks(&lines, &note.headings);
+
+    for line in &lines {
+        let prose = strip_inline_code(line.text);
+        for tag in inline_tags(&prose) {
+            note.tags.push(ParsedTag {
+                tag,
+                line: Some(line.number),
+            });
+        }
+        for (target, kind) in links(&prose) {
+            note.links.push(ParsedLink {
+                target,
+                kind,
+                line: line.number,
+                context: context(line.text),
+            });
+        }
+    }
+
+    note
+}
+
+pub fn body_lines(text: &str) -> Vec<BodyLine<'_>> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    let mut fence: Option<&str> = None;
+
+    let body_start = frontmatter::split(text).map_or(0, |(_, start)| start);
+
+    for (index, raw) in text.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += raw.len();
+        if line_offset < body_start {
+            continue;
+        }
+
+        let line = raw.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim_start();
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+            continue;
+        }
+        if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+            continue;
+        }
+
+        lines.push(BodyLine {
+            number: index + 1,
+            offset: line_offset,
+            text: line,
+        });
+    }
+
+    lines
+}
+
+/// A ```` ``` ```` or `~~~` fenced block.
+#[derive(Debug)]
+pub struct FencedBlock {
+    /// The word after the opening fence, e.g. `rust` or `query`
+    pub info: String,
+    /// 1-based line of the opening fence
+    pub line: usize,
+    /// Byte range of the whole block, fences included
+    pub start: usize,
+    pub end: usize,
+    /// Byte range of the lines between the fences
+    pub content_start: usize,
+    pub content_end: usize,
+}
+
+/// Fenced code blocks in the note body, using the same fence rules as
+/// `body_lines`. An unclosed fence runs to the end of the note.
+pub fn fenced_blocks(text: &str) -> Vec<FencedBlock> {
+    let mut blocks = Vec::new();
+    let mut offset = 0;
+    let mut open: Option<(&str, FencedBlock)> = None;
+
+    let body_start = frontmatter::split(text).map_or(0, |(_, start)| start);
+
+    for (index, raw) in text.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += raw.len();
+        if line_offset < body_start {
+            continue;
+        }
+        let trimmed = raw.trim();
+
+        if let Some((marker, _)) = &open {
+            if trimmed.starts_with(*marker) {
+                if let Some((_, mut block)) = open.take() {
+                    block.content_end = line_offset;
+                    block.end = offset;
+                    blocks.push(block);
+                }
+            }
+            continue;
+        }
+        for marker in ["```", "~~~"] {
+            if let Some(info) = trimmed.strip_prefix(marker) {
+                let info = info.trim_start_matches(&marker[..1]).split_whitespace().next().unwrap_or("");
+                open = Some((
+                    marker,
+                    FencedBlock {
+                        info: info.to_string(),
+                        line: index + 1,
+                        start: line_offset,
+                        end: text.len(),
+                        content_start: offset,
+                        content_end: text.len(),
+                    },
+                ));
+                break;
+            }
+        }
+    }
+
+    blocks.extend(open.map(|(_, block)| block));
+    blocks
+}
+
+/// Blanks out `code spans` so their contents aren't read as markup. Byte
+/// offsets into the result are valid for the original line.
+pub fn strip_inline_code(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut in_code = false;
+
+    for c in line.chars() {
+        if c == '`' {
+            in_code = !in_code;
+            result.push(' ');
+        } else if in_code {
+            result.extend(std::iter::repeat_n(' ', c.len_utf8()));
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// ATX headings (`## Title`) with the extent of their sections.
+fn headings(text: &str, lines: &[BodyLine]) -> Vec<ParsedHeading> {
+    let found: Vec<(usize, &BodyLine, String)> = lines
+        .iter()
+        .filter_map(|line| heading(line.text).map(|(level, title)| (level, line, title)))
+        .collect();
+    let last_line = text.split_inclusive('\n').count().max(1);
+
+    found
+        .iter()
+        .enumerate()
+        .map(|(i, (level, line, title))| {
+            let next = found[i + 1..].iter().find(|(next_level, _, _)| next_level <= level);
+            let (end_line, end_byte) = match next {
+                Some((_, next, _)) => (next.number - 1, next.offset),
+                None => (last_line, text.len()),
+            };
+            ParsedHeading {
+                level: *level,
+                text: title.clone(),
+                range: SourceRange {
+                    start_line: line.number,
+                    end_line,
+                    start_byte: line.offset,
+                    end_byte,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Level and text of an ATX heading line.
+pub fn heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+
+    // Closing hashes are decoration: `## Title ##`
+    let title = rest.trim().trim_end_matches('#').trim_end();
+    Some((level, title.to_string()))
+}
+
+fn tasks(lines: &[BodyLine], headings: &[ParsedHeading]) -> Vec<ParsedTask> {
+    lines
+        .iter()
+        .filter_map(|line| {
+            let marker = task_parser::marker(line.text)?;
+            let (text, fields) = task_parser::fields(&line.text[marker.text_offset..]);
+            let heading = headings
+                .iter()
+                .rev()
+                .find(|h| h.range.start_line < line.number)
+                .map(|h| h.text.clone());
+            Some(ParsedTask {
+                line: line.number,
+                status: marker.status,
+                text,
+                heading,
+                fields,
+            })
+        })
+        .collect()
+}
+
+fn blocks(lines: &[BodyLine]) -> Vec<ParsedBlock> {
+    let mut blocks = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let Some((id, anchor_only)) = block_anchor(line.text) else {
+            continue;
+        };
+
+        // An anchor on its own line labels the paragraph right above it
+        let last = if anchor_only {
+            match i.checked_sub(1) {
+                Some(prev) if lines[prev].number + 1 == line.number && !lines[prev].text.trim().is_empty() => prev,
+                _ => i,
+            }
+        } else {
+            i
+        };
+
+        // List items are blocks of their own; paragraphs extend up to a blank line
+        let mut first = last;
+        if !is_list_item(lines[last].text) {
+            while first > 0 {
+                let prev = &lines[first - 1];
+                let text = prev.text.trim();
+                if prev.number + 1 != lines[first].number
+                    || text.is_empty()
+                    || heading(text).is_some()
+                    || is_list_item(text)
+                {
+                    break;
+                }
+                first -= 1;
+            }
+        }
+
+        blocks.push(ParsedBlock {
+            id,
+            range: SourceRange {
+                start_line: lines[first].number,
+                end_line: line.number,
+                start_byte: lines[first].offset,
+                end_byte: line.offset + line.text.len(),
+            },
+        });
+    }
+
+    blocks
+}
+
+/// `text ^block-id` or a line holding only `^block-id`; the flag is set for
+/// the latter.
+fn block_anchor(line: &str) -> Option<(String, bool)> {
+    let trimmed = line.trim_end();
+    let (before, anchor) = match trimmed.rsplit_once(char::is_whitespace) {
+        Some((before, anchor)) => (before, anchor),
+        None => ("", trimmed),
+    };
+
+    let id = anchor.strip_prefix('^')?;
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+
+    Some((id.to_string(), before.trim().is_empty()))
+}
+
+fn is_list_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ") {
+        return true;
+    }
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    digits > 0 && (trimmed[digits..].starts_with(". ") || trimmed[digits..].starts_with(") "))
+}
+
+const MAX_CONTEXT_LEN: usize = 240;
+
+fn context(line: &str) -> String {
+    let line = line.trim();
+    match line.char_indices().nth(MAX_CONTEXT_LEN) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// A link found on a line, with enough position information to rewrite it.
+#[derive(Debug)]
+pub struct LinkSpan {
+    /// Target as written (percent-decoded for markdown links), with anchor
+    pub target: String,
+    pub kind: LinkKind,
+    /// `[text](dest)` syntax rather than `[[...]]`
+    pub markdown: bool,
+    /// Byte range of the note or file part of the target within the line;
+    /// any `#anchor`, alias or link title lies outside it
+    pub path_start: usize,
+    pub path_end: usize,
+    /// Byte range of the whole link, including the `!` of an embed
+    pub start: usize,
+    pub end: usize,
+}
+
+/// `[[wikilinks]]`, `![[embeds]]` and `[text](relative/path.md)` links in
+/// the order they appear. External URLs and same-note anchors are skipped.
+pub fn links(line: &str) -> Vec<(String, LinkKind)> {
+    link_spans(line)
+        .into_iter()
+        .map(|span| (span.target, span.kind))
+        .collect()
+}
+
+pub fn link_spans(line: &str) -> Vec<LinkSpan> {
+    let mut found: Vec<LinkSpan> = Vec::new();
+
+    let mut pos = 0;
+    while let Some(offset) = line[pos..].find("[[") {
+        let start = pos + offset;
+        let Some(len) = line[start + 2..].find("]]") else {
+            break;
+        };
+        let inner = &line[start + 2..start + 2 + len];
+        pos = start + 2 + len + 2;
+
+        // `\|` is how an alias is written inside tables
+        let target = inner.split('|').next().unwrap_or(inner).trim_end_matches('\\');
+        if target.trim().is_empty() {
+            continue;
+        }
+        let note = target.split('#').next().unwrap_or(target);
+        let path_start = start + 2 + (note.len() - note.trim_start().len());
+        let kind = if line[..start].ends_with('!') {
+            LinkKind::Embed
+        } else {
+            LinkKind::Wikilink
+        };
+        found.push(LinkSpan {
+            target: target.trim().to_string(),
+            kind,
+            markdown: false,
+            path_start,
+            path_end: path_start + note.trim().len(),
+            start: if kind == LinkKind::Embed { start - 1 } else { start },
+            end: pos,
+        });
+    }
+
+    let mut pos = 0;
+    while let Some(offset) = line[pos..].find("](") {
+        let close = pos + offset;
+        let Some(len) = line[close + 2..].find(')') else {
+            break;
+        };
+        let raw = &line[close + 2..close + 2 + len];
+        pos = close + 2 + len + 1;
+
+        let Some(open) = line[..close].rfind('[') else {
+            continue;
+        };
+        // Part of a wikilink that happens to be followed by `(`
+        if line[..close].ends_with(']') {
+            continue;
+        }
+        let Some(target) = markdown_target(raw) else {
+            continue;
+        };
+
+        let mut dest_start = close + 2 + (raw.len() - raw.trim_start().len());
+        let dest = raw.trim();
+        let dest = match dest.strip_prefix('<') {
+            Some(rest) => {
+                dest_start += 1;
+                rest.split('>').next().unwrap_or(rest)
+            }
+            None => dest.split_whitespace().next().unwrap_or(dest),
+        };
+        let path_len = dest.split('#').next().unwrap_or(dest).len();
+
+        let kind = if line[..open].ends_with('!') {
+            LinkKind::Embed
+        } else {
+            LinkKind::Markdown
+        };
+        found.push(LinkSpan {
+            target,
+            kind,
+            markdown: true,
+            path_start: dest_start,
+            path_end: dest_start + path_len,
+            start: if kind == LinkKind::Embed { open - 1 } else { open },
+            end: pos,
+        });
+    }
+
+    found.sort_by_key(|span| span.path_start);
+    found
+}
+
+/// The destination of a markdown link, percent-decoded, or `None` for URLs
+/// and pure anchors.
+fn markdown_target(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    // `<path with spaces.md>` or `path.md "title"`
+    let target = match raw.strip_prefix('<') {
+        Some(rest) => rest.split('>').next().unwrap_or(rest),
+        None => raw.split_whitespace().next().unwrap_or(raw),
+    };
+
+    if target.is_empty() || target.starts_with('#') {
+        return None;
+    }
+    // Any scheme (http:, mailto:, obsidian:) means it isn't a vault path;
+    // single letters are Windows drives
+    if let Some((scheme, _)) = target.split_once(':') {
+        let is_scheme = scheme.len() > 1
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if is_scheme {
+            return None;
+        }
+    }
+
+    Some(percent_decode(target))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit()
+        {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("00");
+            decoded.push(u8::from_str_radix(hex, 16).unwrap_or(0));
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// `#tag` and `#nested/tag` occurrences. A tag starts after whitespace or at
+/// the line start and needs at least one non-digit, so `#1` and headings
+/// (`# Title`) don't count.
+fn inline_tags(line: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        let at_boundary = previous.is_none_or(char::is_whitespace);
+        previous = Some(c);
+        if c != '#' || !at_boundary {
+            continue;
+        }
+
+        let start = index + 1;
+        let mut end = start;
+        while let Some(&(i, next)) = chars.peek() {
+            if !is_tag_char(next) {
+                break;
+            }
+            end = i + next.len_utf8();
+            previous = Some(next);
+            chars.next();
+        }
+
+        if let Some(tag) = normalize_tag(&line[start..end]) {
+            tags.push(tag);
+        }
+    }
+
+    tags
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// Trims stray slashes and a leading `#`; rejects empty and all-digit tags.
+pub fn normalize_tag(raw: &str) -> Option<String> {
+    let tag = raw.trim().trim_start_matches('#').trim_matches('/');
+    if tag.is_empty() || tag.chars().all(|c| c.is_ascii_digit() || c == '/') {
+        return None;
+    }
+    if !tag.chars().all(is_tag_char) {
+        return None;
+    }
+    Some(tag.to_string())
+}
+
+/// `tags:` may be a list or a single comma or space separated string.
+fn frontmatter_tags(value: &serde_json::Value) -> Vec<String> {
+    let raw: Vec<String> = match value {
+        serde_json::Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(|s| s.to_string()))
+            .collect(),
+        serde_json::Value::String(s) => s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(|s| s.to_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    raw.iter().filter_map(|tag| normalize_tag(tag)).collect()
+}
+
+/// `aliases:` is normally a list; a plain string is a single alias.
+fn frontmatter_aliases(value: &serde_json::Value) -> Vec<String> {
+    let raw: Vec<&str> = match value {
+        serde_json::Value::Array(items) => items.iter().filter_map(|item| item.as_str()).collect(),
+        serde_json::Value::String(s) => vec![s.as_str()],
+        _ => Vec::new(),
+    };
+
+    raw.into_iter()
+        .map(|alias| alias.trim().to_string())
+        .filter(|alias| !alias.is_empty())
+        .collect()
+}
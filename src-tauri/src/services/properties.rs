@@ -0,0 +1,99 @@
+use crate::models::note::{Property, PropertyKey, PropertyType};
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+
+/// Replaces the stored properties of a file.
+pub fn replace(conn: &Connection, file_id: i64, properties: &[Property]) -> Result<(), String> {
+    conn.execute("DELETE FROM properties WHERE file_id = ?1", params![file_id])
+        .map_err(|e| format!("Failed to clear properties: {}", e))?;
+
+    let mut stmt = conn
+        .prepare_cached(
+            "INSERT INTO properties (file_id, key, value_type, value, position)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    for (position, property) in properties.iter().enumerate() {
+        stmt.execute(params![
+            file_id,
+            property.key,
+            property.value_type.as_str(),
+            property.value.to_string(),
+            position as i64
+        ])
+        .map_err(|e| format!("Failed to store property: {}", e))?;
+    }
+
+    Ok(())
+}
+
+pub fn for_path(conn: &Connection, path: &str) -> Result<Vec<Property>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.key, p.value_type, p.value FROM properties p
+             JOIN files f ON f.id = p.file_id
+             WHERE f.path = ?1
+             ORDER BY p.position",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let properties = stmt
+        .query_map(params![path], |row| {
+            let value: String = row.get(2)?;
+            Ok(Property {
+                key: row.get(0)?,
+                value_type: PropertyType::parse(&row.get::<_, String>(1)?),
+                value: serde_json::from_str(&value).unwrap_or(serde_json::Value::Null),
+            })
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|p| p.ok())
+        .collect();
+
+    Ok(properties)
+}
+
+/// Every property key in the index with the number of files using it.
+pub fn list_keys(conn: &Connection) -> Result<Vec<PropertyKey>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT key, value_type, COUNT(DISTINCT file_id) FROM properties
+             GROUP BY key, value_type",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok());
+
+    // A key can be typed differently in different notes; report the most common type
+    let mut keys: HashMap<String, (PropertyKey, i64)> = HashMap::new();
+    for (key, value_type, count) in rows {
+        let entry = keys.entry(key.clone()).or_insert_with(|| {
+            let key = PropertyKey {
+                key,
+                value_type: PropertyType::parse(&value_type),
+                file_count: 0,
+            };
+            (key, 0)
+        });
+        if count > entry.1 {
+            entry.0.value_type = PropertyType::parse(&value_type);
+            entry.1 = count;
+        }
+        entry.0.file_count += count;
+    }
+
+    let mut keys: Vec<PropertyKey> = keys.into_values().map(|(key, _)| key).collect();
+    keys.sort_by(|a, b| a.key.cmp(&b.key));
+
+    Ok(keys)
+}
@@ -0,0 +1,358 @@
+use crate::models::query::{QueryResult, QueryResultKind, QueryRow};
+use crate::services::content_extractor::MARKDOWN_MIME;
+use crate::services::link_resolver;
+use crate::services::query_parser::{self, CompareOp, Expr, QueryKind, SortOrder, Source};
+use crate::services::tags;
+use crate::services::task_parser;
+use chrono::{Local, NaiveDate};
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params_from_iter, Connection};
+use std::path::{Path, MAIN_SEPARATOR, MAIN_SEPARATOR_STR};
+
+/// Compiled SQL for an expression. `json` marks SQL yielding stored JSON
+/// (property values, lists) rather than a plain SQL value.
+struct Compiled {
+    sql: String,
+    json: bool,
+}
+
+struct Compiler<'a> {
+    conn: &'a Connection,
+    /// Bound parameters; every literal from the query goes through these
+    values: Vec<Value>,
+    today: NaiveDate,
+    /// The note running the query, for resolving `[[links]]`
+    origin: String,
+}
+
+/// Parses and runs a query over the indexed notes. `origin` is the note the
+/// query is written in, if any; links in `FROM` resolve relative to it.
+pub fn run(conn: &Connection, source: &str, origin: Option<&str>) -> Result<QueryResult, String> {
+    let query = query_parser::parse(source)?;
+
+    let mut compiler = Compiler {
+        conn,
+        values: vec![Value::Text(MARKDOWN_MIME.to_string())],
+        today: Local::now().date_naive(),
+        origin: origin.unwrap_or_default().to_string(),
+    };
+
+    let mut columns = Vec::new();
+    let mut selects = vec!["f.path".to_string()];
+    let mut json_columns = vec![false];
+    if !query.without_id {
+        columns.push("File".to_string());
+    }
+    for field in &query.fields {
+        let compiled = compiler.expr(&field.expr)?;
+        columns.push(field.label.clone());
+        selects.push(compiled.sql);
+        json_columns.push(compiled.json);
+    }
+
+    let mut conditions = vec!["(f.mime_type IS NULL OR f.mime_type = ?1)".to_string()];
+    if let Some(from) = &query.from {
+        conditions.push(compiler.source(from)?);
+    }
+    for filter in &query.filters {
+        conditions.push(compiler.condition(filter)?);
+    }
+
+    let mut order = Vec::new();
+    for (expr, direction) in &query.sort {
+        let sql = compiler.scalar(expr)?;
+        order.push(match direction {
+            SortOrder::Asc => format!("{} ASC", sql),
+            SortOrder::Desc => format!("{} DESC", sql),
+        });
+    }
+    order.push("f.path".to_string());
+
+    let mut sql = format!(
+        "SELECT {} FROM files f WHERE {} ORDER BY {}",
+        selects.join(", "),
+        conditions.join(" AND "),
+        order.join(", ")
+    );
+    if let Some(limit) = query.limit {
+        sql.push_str(&format!(" LIMIT {}", limit));
+    }
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let rows = stmt
+        .query_map(params_from_iter(compiler.values.iter()), |row| {
+            let path: String = row.get(0)?;
+            let mut values = Vec::with_capacity(json_columns.len());
+            if !query.without_id {
+                values.push(serde_json::Value::String(path.clone()));
+            }
+            for (i, json) in json_columns.iter().enumerate().skip(1) {
+                values.push(to_json(row.get_ref(i)?, *json));
+            }
+            Ok(QueryRow { path, values })
+        })
+        .map_err(|e| format!("Failed to run query: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read query results: {}", e))?;
+
+    Ok(QueryResult {
+        kind: match query.kind {
+            QueryKind::Table => QueryResultKind::Table,
+            QueryKind::List => QueryResultKind::List,
+        },
+        columns,
+        rows,
+    })
+}
+
+fn to_json(value: ValueRef, json: bool) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(n) => n.into(),
+        ValueRef::Real(n) => n.into(),
+        ValueRef::Text(bytes) => {
+            let text = String::from_utf8_lossy(bytes);
+            if json {
+                serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text.to_string()))
+            } else {
+                serde_json::Value::String(text.to_string())
+            }
+        }
+        ValueRef::Blob(_) => serde_json::Value::Null,
+    }
+}
+
+impl Compiler<'_> {
+    /// Binds `value` and returns its parameter number.
+    fn bind(&mut self, value: Value) -> usize {
+        self.values.push(value);
+        self.values.len()
+    }
+
+    fn param(&mut self, value: Value) -> String {
+        format!("?{}", self.bind(value))
+    }
+
+    fn source(&mut self, source: &Source) -> Result<String, String> {
+        Ok(match source {
+            Source::Tag(tag) => {
+                let param = self.bind(Value::Text(tags::tag_key(tag)));
+                tags::filter_clause(param)
+            }
+            Source::Folder(folder) => {
+                let folder = folder.trim_end_matches(['/', '\\']).replace(['/', '\\'], MAIN_SEPARATOR_STR);
+                if Path::new(&folder).is_absolute() {
+                    let prefix = self.param(Value::Text(format!("{}{}", folder, MAIN_SEPARATOR)));
+                    format!("substr(f.path, 1, length({0})) = {0}", prefix)
+                } else {
+                    // Folders are written relative to whichever root holds them
+                    let folder = folder.trim_start_matches(MAIN_SEPARATOR);
+                    let relative = self.param(Value::Text(format!("{}{}{}", MAIN_SEPARATOR, folder, MAIN_SEPARATOR)));
+                    let separator = self.param(Value::Text(MAIN_SEPARATOR.to_string()));
+                    format!(
+                        "EXISTS (SELECT 1 FROM roots r
+                             WHERE substr(f.path, 1, length(rtrim(r.path, {1}) || {0})) = rtrim(r.path, {1}) || {0})",
+                        relative, separator
+                    )
+                }
+            }
+            Source::LinksTo(target) => match self.resolve(target)? {
+                Some(path) => {
+                    let param = self.param(Value::Text(path));
+                    format!(
+                        "EXISTS (SELECT 1 FROM links l JOIN files t ON t.id = l.resolved_id
+                             WHERE l.source_id = f.id AND t.path = {})",
+                        param
+                    )
+                }
+                None => "0".to_string(),
+            },
+            Source::LinkedFrom(target) => match self.resolve(target)? {
+                Some(path) => {
+                    let param = self.param(Value::Text(path));
+                    format!(
+                        "f.id IN (SELECT l.resolved_id FROM links l JOIN files s ON s.id = l.source_id
+                             WHERE s.path = {})",
+                        param
+                    )
+                }
+                None => "0".to_string(),
+            },
+            Source::Not(inner) => format!("NOT ({})", self.source(inner)?),
+            Source::And(left, right) => format!("({} AND {})", self.source(left)?, self.source(right)?),
+            Source::Or(left, right) => format!("({} OR {})", self.source(left)?, self.source(right)?),
+        })
+    }
+
+    fn resolve(&self, target: &str) -> Result<Option<String>, String> {
+        Ok(link_resolver::resolve_wikilink(self.conn, target, &self.origin)?.map(|t| t.path))
+    }
+
+    /// SQL for `expr` used as a true/false condition. Plain values count as
+    /// true unless null, zero or empty.
+    fn condition(&mut self, expr: &Expr) -> Result<String, String> {
+        match expr {
+            Expr::Not(inner) => Ok(format!("NOT ({})", self.condition(inner)?)),
+            Expr::And(left, right) => Ok(format!("({} AND {})", self.condition(left)?, self.condition(right)?)),
+            Expr::Or(left, right) => Ok(format!("({} OR {})", self.condition(left)?, self.condition(right)?)),
+            Expr::Compare(..) | Expr::Call(..) if !is_value_call(expr) => Ok(self.expr(expr)?.sql),
+            _ => Ok(format!("(COALESCE({}, 0) NOT IN (0, ''))", self.scalar(expr)?)),
+        }
+    }
+
+    /// SQL yielding a plain SQL value, unwrapping stored JSON.
+    fn scalar(&mut self, expr: &Expr) -> Result<String, String> {
+        let compiled = self.expr(expr)?;
+        Ok(if compiled.json {
+            format!("json_extract({}, '$')", compiled.sql)
+        } else {
+            compiled.sql
+        })
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<Compiled, String> {
+        let plain = |sql: String| Compiled { sql, json: false };
+        Ok(match expr {
+            Expr::Field(name) => self.field(name)?,
+            Expr::String(text) => plain(self.param(Value::Text(text.clone()))),
+            Expr::Number(n) if n.fract() == 0.0 => plain(self.param(Value::Integer(*n as i64))),
+            Expr::Number(n) => plain(self.param(Value::Real(*n))),
+            Expr::Bool(b) => plain(if *b { "1" } else { "0" }.to_string()),
+            Expr::Null => plain("NULL".to_string()),
+            Expr::Not(_) | Expr::And(..) | Expr::Or(..) => plain(self.condition(expr)?),
+            Expr::Compare(left, op, right) => {
+                let left = self.scalar(left)?;
+                let right = self.scalar(right)?;
+                let op = match op {
+                    CompareOp::Eq => "IS",
+                    CompareOp::Ne => "IS NOT",
+                    CompareOp::Lt => "<",
+                    CompareOp::Le => "<=",
+                    CompareOp::Gt => ">",
+                    CompareOp::Ge => ">=",
+                };
+                plain(format!("({} {} {})", left, op, right))
+            }
+            Expr::Call(name, args) => self.call(name, args)?,
+        })
+    }
+
+    fn call(&mut self, name: &str, args: &[Expr]) -> Result<Compiled, String> {
+        let plain = |sql: String| Compiled { sql, json: false };
+        match (name, args) {
+            ("date", [arg]) => {
+                // `date(today)`, `date(2026-01-01)`, `date("in 2 weeks")`
+                let literal = match arg {
+                    Expr::Field(text) | Expr::String(text) => task_parser::relative_date(text, self.today),
+                    _ => None,
+                };
+                Ok(match literal {
+                    Some(date) => plain(self.param(Value::Text(date.format("%Y-%m-%d").to_string()))),
+                    None => match arg {
+                        Expr::String(text) => return Err(format!("Invalid date: {}", text)),
+                        _ => plain(format!("date({})", self.scalar(arg)?)),
+                    },
+                })
+            }
+            ("contains", [list, item]) => {
+                if let (Expr::Field(field), Expr::String(tag)) = (list, item) {
+                    if field.eq_ignore_ascii_case("file.tags") {
+                        let param = self.bind(Value::Text(tags::tag_key(tag)));
+                        return Ok(plain(tags::filter_clause(param)));
+                    }
+                }
+                let list = self.expr(list)?;
+                let item = self.scalar(item)?;
+                Ok(plain(if list.json {
+                    format!(
+                        "(CASE WHEN json_type({0}) = 'array'
+                              THEN EXISTS (SELECT 1 FROM json_each({0}) WHERE json_each.value = {1})
+                              ELSE instr(json_extract({0}, '$'), {1}) > 0 END)",
+                        list.sql, item
+                    )
+                } else {
+                    format!("(instr({}, {}) > 0)", list.sql, item)
+                }))
+            }
+            ("length", [value]) => {
+                let value = self.expr(value)?;
+                Ok(plain(if value.json {
+                    format!(
+                        "(CASE WHEN json_type({0}) = 'array' THEN json_array_length({0})
+                              ELSE length(json_extract({0}, '$')) END)",
+                        value.sql
+                    )
+                } else {
+                    format!("length({})", value.sql)
+                }))
+            }
+            ("lower", [value]) => Ok(plain(format!("lower({})", self.scalar(value)?))),
+            ("upper", [value]) => Ok(plain(format!("upper({})", self.scalar(value)?))),
+            ("date" | "contains" | "length" | "lower" | "upper", _) => {
+                Err(format!("Wrong number of arguments to {}()", name))
+            }
+            _ => Err(format!("Unknown function {}()", name)),
+        }
+    }
+
+    /// `file.*` fields come from the index; anything else is a frontmatter
+    /// property, matched case-insensitively.
+    fn field(&mut self, name: &str) -> Result<Compiled, String> {
+        let plain = |sql: &str| Compiled { sql: sql.to_string(), json: false };
+        let json = |sql: &str| Compiled { sql: sql.to_string(), json: true };
+
+        let Some(file_field) = name.strip_prefix("file.") else {
+            let key = self.param(Value::Text(name.to_string()));
+            return Ok(json(&format!(
+                "(SELECT p.value FROM properties p WHERE p.file_id = f.id AND lower(p.key) = lower({}) LIMIT 1)",
+                key
+            )));
+        };
+
+        // SQLite has no basename(): trimming every character but the
+        // separator off the end leaves the folder
+        let folder = |compiler: &mut Self| {
+            let separator = compiler.param(Value::Text(MAIN_SEPARATOR.to_string()));
+            (format!("rtrim(f.path, replace(f.path, {}, ''))", separator), separator)
+        };
+        Ok(match file_field.to_lowercase().as_str() {
+            "path" | "link" => plain("f.path"),
+            "name" => {
+                let (folder, _) = folder(self);
+                let file_name = format!("substr(f.path, length({}) + 1)", folder);
+                plain(&format!(
+                    "(CASE WHEN lower({0}) LIKE '%.md' THEN substr({0}, 1, length({0}) - 3) ELSE {0} END)",
+                    file_name
+                ))
+            }
+            "folder" => {
+                let (folder, separator) = folder(self);
+                plain(&format!("rtrim({}, {})", folder, separator))
+            }
+            "title" => plain("f.title"),
+            "mtime" => plain("datetime(f.modified, 'unixepoch', 'localtime')"),
+            "mday" => plain("date(f.modified, 'unixepoch', 'localtime')"),
+            "ctime" => plain("datetime(f.created, 'unixepoch', 'localtime')"),
+            "cday" => plain("date(f.created, 'unixepoch', 'localtime')"),
+            "tags" => json("(SELECT json_group_array(DISTINCT '#' || t.tag) FROM tags t WHERE t.file_id = f.id)"),
+            "aliases" => json("(SELECT json_group_array(a.alias) FROM aliases a WHERE a.file_id = f.id)"),
+            "outlinks" => json(
+                "(SELECT json_group_array(DISTINCT t.path) FROM links l JOIN files t ON t.id = l.resolved_id
+                  WHERE l.source_id = f.id)",
+            ),
+            "inlinks" => json(
+                "(SELECT json_group_array(DISTINCT s.path) FROM links l JOIN files s ON s.id = l.source_id
+                  WHERE l.resolved_id = f.id)",
+            ),
+            _ => return Err(format!("Unknown field {}", name)),
+        })
+    }
+}
+
+/// Calls that produce a value rather than a condition.
+fn is_value_call(expr: &Expr) -> bool {
+    matches!(expr, Expr::Call(name, _) if name != "contains")
+}
@@ -0,0 +1,517 @@
+/// A parsed query, e.g.
+/// `TABLE status, due AS "Due" FROM #project AND -"Archive" WHERE due < date(today) SORT due DESC LIMIT 20`.
+#[derive(Debug)]
+pub struct Query {
+    pub kind: QueryKind,
+    /// `TABLE WITHOUT ID` leaves out the file column
+    pub without_id: bool,
+    pub fields: Vec<QueryField>,
+    pub from: Option<Source>,
+    pub filters: Vec<Expr>,
+    pub sort: Vec<(Expr, SortOrder)>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueryKind {
+    Table,
+    List,
+}
+
+#[derive(Debug)]
+pub struct QueryField {
+    pub expr: Expr,
+    /// The `AS` name, or the expression as written
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Which notes a query looks at.
+#[derive(Debug)]
+pub enum Source {
+    /// `#tag`, including nested tags
+    Tag(String),
+    /// `"folder"`, relative to an indexed root unless absolute
+    Folder(String),
+    /// `[[Note]]`: notes linking to it
+    LinksTo(String),
+    /// `outgoing([[Note]])`: notes it links to
+    LinkedFrom(String),
+    Not(Box<Source>),
+    And(Box<Source>, Box<Source>),
+    Or(Box<Source>, Box<Source>),
+}
+
+#[derive(Debug)]
+pub enum Expr {
+    /// A frontmatter property or an implicit `file.*` field
+    Field(String),
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Keywords, field names, bare dates and the like
+    Word(String),
+    String(String),
+    Number(f64),
+    Tag(String),
+    Link(String),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 11] = ["!=", "<=", ">=", "=", "<", ">", "!", ",", "(", ")", "-"];
+
+/// Deepest nesting of brackets, negations and chained AND/OR a query may
+/// have. Queries come from note contents, and parsing and evaluating them
+/// recurse once per level.
+const MAX_DEPTH: usize = 100;
+
+pub fn parse(source: &str) -> Result<Query, String> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        source,
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let query = parser.query()?;
+    match parser.peek() {
+        None => Ok(query),
+        Some(_) => Err(parser.unexpected()),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => {
+                        if let Some((_, escaped)) = chars.next() {
+                            text.push(escaped);
+                        }
+                    }
+                    Some((_, c)) => text.push(c),
+                    None => return Err(format!("Unterminated string at {}", start)),
+                }
+            }
+            tokens.push((Token::String(text), start));
+            continue;
+        }
+
+        if source[start..].starts_with("[[") {
+            let Some(len) = source[start + 2..].find("]]") else {
+                return Err(format!("Unterminated link at {}", start));
+            };
+            let inner = &source[start + 2..start + 2 + len];
+            let target = inner.split('|').next().unwrap_or(inner).trim();
+            tokens.push((Token::Link(target.to_string()), start));
+            let end = start + 2 + len + 2;
+            while chars.peek().is_some_and(|&(i, _)| i < end) {
+                chars.next();
+            }
+            continue;
+        }
+
+        if c == '#' {
+            chars.next();
+            let mut tag = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || matches!(c, '_' | '-' | '/')) {
+                    break;
+                }
+                tag.push(c);
+                chars.next();
+            }
+            if tag.is_empty() {
+                return Err(format!("Expected a tag name at {}", start));
+            }
+            tokens.push((Token::Tag(tag), start));
+            continue;
+        }
+
+        if c.is_alphanumeric() || c == '_' {
+            let mut word = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            // Only words starting with a digit are numbers; `inf` and `nan` are fields
+            let token = match word.parse::<f64>() {
+                Ok(number) if word.starts_with(|c: char| c.is_ascii_digit()) => Token::Number(number),
+                _ => Token::Word(word),
+            };
+            tokens.push((token, start));
+            continue;
+        }
+
+        let Some(symbol) = SYMBOLS.iter().find(|s| source[start..].starts_with(**s)) else {
+            return Err(format!("Unexpected '{}' at {}", c, start));
+        };
+        tokens.push((Token::Symbol(symbol), start));
+        for _ in 0..symbol.len() {
+            chars.next();
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn query(&mut self) -> Result<Query, String> {
+        let kind = if self.keyword("TABLE") {
+            QueryKind::Table
+        } else if self.keyword("LIST") {
+            QueryKind::List
+        } else {
+            return Err("Query must start with TABLE or LIST".to_string());
+        };
+
+        let mut without_id = false;
+        if kind == QueryKind::Table && self.keyword("WITHOUT") {
+            self.expect_keyword("ID")?;
+            without_id = true;
+        }
+
+        let mut fields = Vec::new();
+        if !self.at_clause() {
+            loop {
+                fields.push(self.field()?);
+                if kind == QueryKind::List || !self.symbol(",") {
+                    break;
+                }
+            }
+        }
+
+        let mut query = Query {
+            kind,
+            without_id,
+            fields,
+            from: None,
+            filters: Vec::new(),
+            sort: Vec::new(),
+            limit: None,
+        };
+
+        while self.peek().is_some() {
+            if self.keyword("FROM") {
+                if query.from.is_some() {
+                    return Err("Only one FROM clause is allowed".to_string());
+                }
+                query.from = Some(self.source_or()?);
+            } else if self.keyword("WHERE") {
+                query.filters.push(self.expr()?);
+            } else if self.keyword("SORT") {
+                loop {
+                    let expr = self.expr()?;
+                    let order = if self.keyword("DESC") || self.keyword("DESCENDING") {
+                        SortOrder::Desc
+                    } else {
+                        let _ = self.keyword("ASC") || self.keyword("ASCENDING");
+                        SortOrder::Asc
+                    };
+                    query.sort.push((expr, order));
+                    if !self.symbol(",") {
+                        break;
+                    }
+                }
+            } else if self.keyword("LIMIT") {
+                match self.next() {
+                    Some(Token::Number(n)) if n >= 0.0 && n.fract() == 0.0 => query.limit = Some(n as usize),
+                    _ => return Err("LIMIT needs a whole number".to_string()),
+                }
+            } else {
+                return Err(self.unexpected());
+            }
+        }
+
+        Ok(query)
+    }
+
+    fn field(&mut self) -> Result<QueryField, String> {
+        let start = self.offset();
+        let expr = self.expr()?;
+        let end = self.offset();
+        let label = if self.keyword("AS") {
+            match self.next() {
+                Some(Token::String(name)) | Some(Token::Word(name)) => name,
+                _ => return Err("Expected a column name after AS".to_string()),
+            }
+        } else {
+            self.source[start..end].trim().to_string()
+        };
+        Ok(QueryField { expr, label })
+    }
+
+    fn source_or(&mut self) -> Result<Source, String> {
+        let depth = self.depth;
+        let mut left = self.source_and()?;
+        while self.keyword("OR") {
+            self.deeper()?;
+            left = Source::Or(Box::new(left), Box::new(self.source_and()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn source_and(&mut self) -> Result<Source, String> {
+        let depth = self.depth;
+        let mut left = self.source_term()?;
+        while self.keyword("AND") {
+            self.deeper()?;
+            left = Source::And(Box::new(left), Box::new(self.source_term()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn source_term(&mut self) -> Result<Source, String> {
+        let depth = self.depth;
+        if self.symbol("-") || self.symbol("!") {
+            self.deeper()?;
+            let inner = self.source_term()?;
+            self.depth = depth;
+            return Ok(Source::Not(Box::new(inner)));
+        }
+        if self.symbol("(") {
+            self.deeper()?;
+            let inner = self.source_or()?;
+            self.expect_symbol(")")?;
+            self.depth = depth;
+            return Ok(inner);
+        }
+        if self.keyword("outgoing") {
+            self.expect_symbol("(")?;
+            let Some(Token::Link(target)) = self.next() else {
+                return Err("outgoing() needs a [[link]]".to_string());
+            };
+            self.expect_symbol(")")?;
+            return Ok(Source::LinkedFrom(target));
+        }
+
+        match self.next() {
+            Some(Token::Tag(tag)) => Ok(Source::Tag(tag)),
+            Some(Token::String(folder)) => Ok(Source::Folder(folder)),
+            Some(Token::Link(target)) => Ok(Source::LinksTo(target)),
+            _ => {
+                self.pos = self.pos.saturating_sub(1);
+                Err(self.unexpected())
+            }
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let depth = self.depth;
+        let mut left = self.and_expr()?;
+        while self.keyword("OR") {
+            self.deeper()?;
+            left = Expr::Or(Box::new(left), Box::new(self.and_expr()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, String> {
+        let depth = self.depth;
+        let mut left = self.not_expr()?;
+        while self.keyword("AND") {
+            self.deeper()?;
+            left = Expr::And(Box::new(left), Box::new(self.not_expr()?));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn not_expr(&mut self) -> Result<Expr, String> {
+        if self.symbol("!") || self.keyword("NOT") {
+            let depth = self.depth;
+            self.deeper()?;
+            let inner = self.not_expr()?;
+            self.depth = depth;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        let left = self.primary()?;
+        let op = match self.peek() {
+            Some(Token::Symbol("=")) => CompareOp::Eq,
+            Some(Token::Symbol("!=")) => CompareOp::Ne,
+            Some(Token::Symbol("<")) => CompareOp::Lt,
+            Some(Token::Symbol("<=")) => CompareOp::Le,
+            Some(Token::Symbol(">")) => CompareOp::Gt,
+            Some(Token::Symbol(">=")) => CompareOp::Ge,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        Ok(Expr::Compare(Box::new(left), op, Box::new(self.primary()?)))
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let depth = self.depth;
+        if self.symbol("(") {
+            self.deeper()?;
+            let inner = self.expr()?;
+            self.expect_symbol(")")?;
+            self.depth = depth;
+            return Ok(inner);
+        }
+        if self.symbol("-") {
+            return match self.next() {
+                Some(Token::Number(n)) => Ok(Expr::Number(-n)),
+                _ => Err("Expected a number after '-'".to_string()),
+            };
+        }
+
+        match self.next() {
+            Some(Token::String(text)) => Ok(Expr::String(text)),
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Word(word)) => {
+                if self.symbol("(") {
+                    self.deeper()?;
+                    let mut args = Vec::new();
+                    if !self.symbol(")") {
+                        loop {
+                            args.push(self.expr()?);
+                            if self.symbol(")") {
+                                break;
+                            }
+                            self.expect_symbol(",")?;
+                        }
+                    }
+                    self.depth = depth;
+                    return Ok(Expr::Call(word.to_lowercase(), args));
+                }
+                Ok(match word.to_lowercase().as_str() {
+                    "true" => Expr::Bool(true),
+                    "false" => Expr::Bool(false),
+                    "null" => Expr::Null,
+                    _ => Expr::Field(word),
+                })
+            }
+            _ => {
+                self.pos = self.pos.saturating_sub(1);
+                Err(self.unexpected())
+            }
+        }
+    }
+
+    /// Enters one more level of nesting; callers restore `depth` on the way out.
+    fn deeper(&mut self) -> Result<(), String> {
+        if self.depth == MAX_DEPTH {
+            return Err("Query is nested too deeply".to_string());
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(token, _)| token.clone());
+        self.pos += 1;
+        token
+    }
+
+    /// Byte offset of the next token, or the end of the source.
+    fn offset(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.source.len(), |&(_, offset)| offset)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.keyword(keyword) {
+            Ok(())
+        } else {
+            Err(format!("Expected {} at {}", keyword, self.offset()))
+        }
+    }
+
+    fn symbol(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), String> {
+        if self.symbol(symbol) {
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' at {}", symbol, self.offset()))
+        }
+    }
+
+    fn at_clause(&self) -> bool {
+        match self.peek() {
+            None => true,
+            Some(Token::Word(word)) => ["FROM", "WHERE", "SORT", "LIMIT"]
+                .iter()
+                .any(|clause| word.eq_ignore_ascii_case(clause)),
+            _ => false,
+        }
+    }
+
+    fn unexpected(&self) -> String {
+        let offset = self.offset();
+        match self.source[offset..].split_whitespace().next() {
+            Some(word) => format!("Unexpected '{}' at {}", word, offset),
+            None => "Unexpected end of query".to_string(),
+        }
+    }
+}
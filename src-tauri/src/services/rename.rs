@@ -0,0 +1,245 @@
+use crate::models::file::{ModifiedFile, RenameResult};
+use crate::models::note::LinkKind;
+use crate::services::file_indexer::{move_path, reindex_file};
+use crate::services::link_resolver::normalize_key;
+use crate::services::links;
+use crate::services::note_parser::{link_spans, strip_inline_code, LinkSpan};
+use rusqlite::Connection;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path};
+
+/// A stored link whose text may need to change because its source or its
+/// target moves.
+struct AffectedLink {
+    line: usize,
+    target: String,
+    kind: LinkKind,
+    old_target: String,
+    /// Where the linked file will live after the move
+    new_target: String,
+}
+
+/// Moves a file or folder and rewrites every link into or out of the moved
+/// notes so they keep pointing at the same files. Aliases and anchors in the
+/// rewritten links are kept as they were.
+pub fn rename_path(conn: &Connection, from: &Path, to: &Path) -> Result<RenameResult, String> {
+    if !from.exists() {
+        return Err("Source does not exist".to_string());
+    }
+    if to.exists() {
+        return Err("Destination already exists".to_string());
+    }
+    if to.starts_with(from) {
+        return Err("Cannot move a folder into itself".to_string());
+    }
+    let from_str = from.to_str().ok_or_else(|| "Invalid file path".to_string())?;
+    let to_str = to.to_str().ok_or_else(|| "Invalid file path".to_string())?;
+
+    let affected = affected_links(conn, from_str, to_str)?;
+
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    fs::rename(from, to).map_err(|e| format!("Failed to move: {}", e))?;
+
+    // Nothing has been rewritten yet, so a failed index update (e.g. the
+    // database is locked by a crawl) can still be undone by moving back
+    if let Err(e) = move_path(conn, from, to) {
+        return Err(match fs::rename(to, from) {
+            Ok(()) => e,
+            Err(undo) => format!("{}; the move could not be undone ({}), it is now at {}", e, undo, to_str),
+        });
+    }
+
+    let mut modified_files = Vec::new();
+    for (source, source_links) in affected {
+        match rewrite_links(Path::new(&source), &source_links) {
+            Ok(Some(previous_content)) => {
+                if let Err(e) = reindex_file(conn, Path::new(&source)) {
+                    eprintln!("Failed to index {:?}: {}", source, e);
+                }
+                modified_files.push(ModifiedFile {
+                    path: source,
+                    previous_content,
+                });
+            }
+            Ok(None) => {}
+            // The move itself succeeded; report what was rewritten so far
+            Err(e) => eprintln!("Failed to rewrite links in {:?}: {}", source, e),
+        }
+    }
+    // Links were rewritten on disk either way; the next index pass catches up
+    if let Err(e) = links::resolve_pending(conn) {
+        eprintln!("Failed to resolve links: {}", e);
+    }
+
+    modified_files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(RenameResult {
+        from: from_str.to_string(),
+        to: to_str.to_string(),
+        modified_files,
+    })
+}
+
+/// Resolved links that touch the moved files, grouped by the post-move path
+/// of the note containing them.
+fn affected_links(
+    conn: &Connection,
+    from: &str,
+    to: &str,
+) -> Result<HashMap<String, Vec<AffectedLink>>, String> {
+    let prefix = format!("{}{}", from.trim_end_matches(std::path::MAIN_SEPARATOR), std::path::MAIN_SEPARATOR);
+    let moved_path = |path: &str| -> String {
+        if path == from || path.starts_with(&prefix) {
+            format!("{}{}", to, &path[from.len()..])
+        } else {
+            path.to_string()
+        }
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.path, l.line, l.target, l.kind, t.path FROM links l
+             JOIN files s ON s.id = l.source_id
+             JOIN files t ON t.id = l.resolved_id
+             WHERE s.path = ?1 OR substr(s.path, 1, length(?2)) = ?2
+                OR t.path = ?1 OR substr(t.path, 1, length(?2)) = ?2",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![from, prefix], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok());
+
+    let mut affected: HashMap<String, Vec<AffectedLink>> = HashMap::new();
+    for (source, line, target, kind, target_path) in rows {
+        affected.entry(moved_path(&source)).or_default().push(AffectedLink {
+            line: line as usize,
+            target,
+            kind: LinkKind::parse(&kind),
+            new_target: moved_path(&target_path),
+            old_target: target_path,
+        });
+    }
+
+    Ok(affected)
+}
+
+/// Rewrites the links of one note in place. Returns the previous content if
+/// anything changed.
+fn rewrite_links(source: &Path, affected: &[AffectedLink]) -> Result<Option<String>, String> {
+    let content = fs::read_to_string(source).map_err(|e| format!("Failed to read file: {}", e))?;
+    let source_dir = source.parent().unwrap_or(Path::new(""));
+
+    let lines: HashSet<usize> = affected.iter().map(|link| link.line).collect();
+    let mut rewritten = String::with_capacity(content.len());
+    let mut changed = false;
+
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        if !lines.contains(&(index + 1)) {
+            rewritten.push_str(line);
+            continue;
+        }
+
+        let mut line = line.to_string();
+        let spans = link_spans(&strip_inline_code(&line));
+        // Right to left so earlier byte ranges stay valid
+        for span in spans.iter().rev() {
+            let Some(link) = affected
+                .iter()
+                .find(|l| l.line == index + 1 && l.kind == span.kind && l.target == span.target)
+            else {
+                continue;
+            };
+            let old_text = &line[span.path_start..span.path_end];
+            let new_text = link_text(span, old_text, source_dir, link, &line);
+            if new_text != old_text {
+                line.replace_range(span.path_start..span.path_end, &new_text);
+                changed = true;
+            }
+        }
+        rewritten.push_str(&line);
+    }
+
+    if !changed {
+        return Ok(None);
+    }
+    fs::write(source, &rewritten).map_err(|e| format!("Failed to write file: {}", e))?;
+    Ok(Some(content))
+}
+
+/// New text for the path part of a link so it names `target` again, in the
+/// same style as `old_text`.
+fn link_text(span: &LinkSpan, old_text: &str, source_dir: &Path, link: &AffectedLink, line: &str) -> String {
+    let target = Path::new(&link.new_target);
+    if span.markdown {
+        let relative = relative_path(source_dir, target);
+        // `<...>` destinations may contain spaces; bare ones must be encoded
+        if line[..span.path_start].ends_with('<') {
+            relative
+        } else {
+            relative.replace(' ', "%20")
+        }
+    } else if old_text.trim().is_empty() || !names_file(old_text, Path::new(&link.old_target)) {
+        // Same-note anchors, and links that resolved through an alias
+        old_text.to_string()
+    } else {
+        wikilink_text(old_text, target)
+    }
+}
+
+/// Whether a wikilink spells out the file name of `target` rather than an alias.
+fn names_file(text: &str, target: &Path) -> bool {
+    let text = text.replace('\\', "/");
+    let name = normalize_key(text.rsplit('/').next().unwrap_or(&text));
+    let file_name = target.file_name().map(|n| normalize_key(&n.to_string_lossy()));
+    let stem = target.file_stem().map(|n| normalize_key(&n.to_string_lossy()));
+
+    Some(&name) == file_name.as_ref() || Some(&name) == stem.as_ref()
+}
+
+/// Bare names stay bare; `folder/Note` keeps as many path segments as before.
+/// The `.md` extension is only written if the old link had one.
+fn wikilink_text(old_text: &str, target: &Path) -> String {
+    let segments: Vec<String> = target
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    let old = old_text.replace('\\', "/");
+    let keep = old.trim_matches('/').split('/').count().clamp(1, segments.len().max(1));
+    let mut text = segments[segments.len().saturating_sub(keep)..].join("/");
+
+    let lower_old = old.to_lowercase();
+    let had_extension = lower_old.ends_with(".md") || lower_old.ends_with(".markdown");
+    let lower = text.to_lowercase();
+    if !had_extension && (lower.ends_with(".md") || lower.ends_with(".markdown")) {
+        text.truncate(text.rfind('.').unwrap_or(text.len()));
+    }
+
+    text
+}
+
+/// `target` relative to `from_dir`, with `/` separators.
+fn relative_path(from_dir: &Path, target: &Path) -> String {
+    let from: Vec<Component> = from_dir.components().collect();
+    let to: Vec<Component> = target.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(to[common..].iter().map(|c| c.as_os_str().to_string_lossy().to_string()));
+    parts.join("/")
+}
@@ -0,0 +1,210 @@
+use crate::models::note::{LinkKind, RenderedNote, SkippedEmbed, SourceSegment};
+use crate::models::query::QueryBlock;
+use crate::services::anchors;
+use crate::services::frontmatter;
+use crate::services::link_resolver::{is_markdown, LinkIndex};
+use crate::services::note_parser::{self, link_spans, strip_inline_code, FencedBlock};
+use crate::services::query;
+use rusqlite::Connection;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+/// Embeds nested deeper than this are left as links.
+const MAX_EMBED_DEPTH: usize = 5;
+
+/// Part of a note chosen by an embed: the whole body or one section or block.
+struct Excerpt {
+    path: String,
+    text: String,
+    range: Range<usize>,
+    /// `None` for the whole note; identifies the excerpt for cycle detection
+    anchor: Option<usize>,
+}
+
+struct Renderer<'a> {
+    conn: &'a Connection,
+    index: &'a LinkIndex,
+    content: String,
+    source_map: Vec<SourceSegment>,
+    skipped_embeds: Vec<SkippedEmbed>,
+    query_blocks: Vec<QueryBlock>,
+    /// Excerpts currently being expanded, outermost first
+    stack: Vec<(String, Option<usize>)>,
+}
+
+/// Reads the note at `path` and expands its `![[embeds]]` of other notes,
+/// headings and blocks, recursively. Embedded text is read from disk so it
+/// reflects edits the index hasn't picked up yet; only link resolution uses the index.
+/// Attachments (images, PDFs) and markdown-style embeds are left for the
+/// preview to display. ```` ```query ```` blocks, in the note or in what it
+/// embeds, are run; a failing query is reported on its block alone.
+pub fn render_note(conn: &Connection, path: &str) -> Result<RenderedNote, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let index = LinkIndex::load(conn)?;
+
+    let mut renderer = Renderer {
+        conn,
+        index: &index,
+        content: String::with_capacity(text.len()),
+        source_map: Vec::new(),
+        skipped_embeds: Vec::new(),
+        query_blocks: Vec::new(),
+        stack: vec![(path.to_string(), None)],
+    };
+    renderer.expand(path, &text, 0..text.len(), 0);
+
+    Ok(RenderedNote {
+        path: path.to_string(),
+        content: renderer.content,
+        source_map: renderer.source_map,
+        skipped_embeds: renderer.skipped_embeds,
+        query_blocks: renderer.query_blocks,
+    })
+}
+
+impl Renderer<'_> {
+    /// Appends `text[range]` with its embeds replaced by what they point at.
+    fn expand(&mut self, path: &str, text: &str, range: Range<usize>, depth: usize) {
+        let mut cursor = range.start;
+        let queries: Vec<FencedBlock> = note_parser::fenced_blocks(text)
+            .into_iter()
+            .filter(|block| block.info.eq_ignore_ascii_case("query"))
+            .collect();
+
+        for line in note_parser::body_lines(text) {
+            if line.offset < range.start || line.offset >= range.end {
+                continue;
+            }
+            let prose = strip_inline_code(line.text);
+            for span in link_spans(&prose) {
+                if span.kind != LinkKind::Embed || span.markdown {
+                    continue;
+                }
+                let (start, end) = (line.offset + span.start, line.offset + span.end);
+                if end > range.end {
+                    continue;
+                }
+
+                let excerpt = match self.excerpt(path, &span.target) {
+                    Ok(Some(excerpt)) => excerpt,
+                    Ok(None) => continue,
+                    Err(reason) => {
+                        self.skip(path, line.number, &span.target, &reason);
+                        continue;
+                    }
+                };
+                let key = (excerpt.path.clone(), excerpt.anchor);
+                if self.stack.contains(&key) {
+                    self.skip(path, line.number, &span.target, "Embeds itself");
+                    continue;
+                }
+                if depth >= MAX_EMBED_DEPTH {
+                    self.skip(path, line.number, &span.target, "Nested too deeply");
+                    continue;
+                }
+
+                self.copy(path, text, cursor..start, depth, &queries);
+                self.stack.push(key);
+                self.expand(&excerpt.path, &excerpt.text, excerpt.range, depth + 1);
+                self.stack.pop();
+                cursor = end;
+            }
+        }
+
+        self.copy(path, text, cursor..range.end, depth, &queries);
+    }
+
+    /// Finds what an embed in `source` shows. `Ok(None)` means it isn't a
+    /// note (an image, say) and stays as written.
+    fn excerpt(&self, source: &str, target: &str) -> Result<Option<Excerpt>, String> {
+        let (note, anchor) = anchors::split_link(target);
+
+        let path = if note.is_empty() {
+            source.to_string()
+        } else {
+            let current_dir = Path::new(source).parent().unwrap_or(Path::new(""));
+            match self.index.candidates(current_dir, note).into_iter().next() {
+                Some(best) => best.path,
+                // Attachments aren't always indexed
+                None if Path::new(note).extension().is_some() && !is_markdown(Path::new(note)) => {
+                    return Ok(None)
+                }
+                None => return Err("Note not found".to_string()),
+            }
+        };
+        if !is_markdown(Path::new(&path)) {
+            return Ok(None);
+        }
+
+        let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+        let range = match anchor {
+            Some(anchor) => {
+                let parsed = note_parser::parse(&text, &[]);
+                let found = anchors::find_in(&parsed, anchor)
+                    .ok_or_else(|| format!("No heading or block \"{}\"", anchor))?;
+                found.start_byte..found.end_byte.min(text.len())
+            }
+            None => frontmatter::split(&text).map_or(0, |(_, start)| start)..text.len(),
+        };
+
+        Ok(Some(Excerpt {
+            path,
+            anchor: anchor.map(|_| range.start),
+            text,
+            range,
+        }))
+    }
+
+    fn copy(
+        &mut self,
+        path: &str,
+        text: &str,
+        range: Range<usize>,
+        depth: usize,
+        queries: &[FencedBlock],
+    ) {
+        if range.is_empty() {
+            return;
+        }
+        let start = self.content.len();
+
+        for block in queries {
+            if block.start < range.start || block.end > range.end {
+                continue;
+            }
+            let source = &text[block.content_start..block.content_end];
+            let (result, error) = match query::run(self.conn, source, Some(path)) {
+                Ok(result) => (Some(result), None),
+                Err(e) => (None, Some(e)),
+            };
+            self.query_blocks.push(QueryBlock {
+                path: path.to_string(),
+                line: block.line,
+                start: start + (block.start - range.start),
+                end: start + (block.end - range.start),
+                source: source.to_string(),
+                result,
+                error,
+            });
+        }
+
+        self.content.push_str(&text[range.clone()]);
+        self.source_map.push(SourceSegment {
+            start,
+            end: self.content.len(),
+            path: path.to_string(),
+            source_start: range.start,
+            depth,
+        });
+    }
+
+    fn skip(&mut self, path: &str, line: usize, target: &str, reason: &str) {
+        self.skipped_embeds.push(SkippedEmbed {
+            path: path.to_string(),
+            line,
+            target: target.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+}
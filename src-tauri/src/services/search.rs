@@ -0,0 +1,206 @@
+use crate::models::search::ContentMatch;
+use crate::services::query_parser::CompareOp;
+use crate::services::search_parser::{self, SearchExpr};
+use crate::services::tags;
+use chrono::Local;
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+
+struct Compiler {
+    /// Bound parameters; nothing the user typed is spliced into SQL or FTS syntax
+    values: Vec<Value>,
+}
+
+/// Runs a content search (see `search_parser`), optionally restricted to
+/// notes carrying every tag in `tags`. Results with text matches rank by
+/// relevance; the rest follow, most recently modified first.
+pub fn content(conn: &Connection, query: &str, tags: &[String]) -> Result<Vec<ContentMatch>, String> {
+    let Some(expr) = search_parser::parse(query, Local::now().date_naive())? else {
+        return Ok(Vec::new());
+    };
+
+    // Text terms every result must contain are searched for once, up front;
+    // the rest of the query filters what that search found
+    let terms = match expr {
+        SearchExpr::And(items) => items,
+        expr => vec![expr],
+    };
+    let (required, rest): (Vec<_>, Vec<_>) = terms.into_iter().partition(|term| fts(term).is_some());
+
+    let mut compiler = Compiler { values: Vec::new() };
+    let mut conditions: Vec<String> = rest.iter().map(|term| compiler.condition(term)).collect();
+    for tag in tags {
+        let key = tags::tag_key(tag);
+        if !key.is_empty() {
+            let param = compiler.bind(Value::Text(key));
+            conditions.push(tags::filter_clause(param));
+        }
+    }
+
+    let sql = if !required.is_empty() {
+        let fts_query: Vec<String> = required.iter().filter_map(fts).collect();
+        let param = compiler.bind(Value::Text(fts_query.join(" AND ")));
+        conditions.insert(0, format!("files_fts MATCH ?{}", param));
+        // CROSS JOIN keeps the full-text search as the outer loop
+        format!(
+            "SELECT f.path, f.title, snippet(files_fts, 2, '<mark>', '</mark>', '...', 32)
+             FROM files_fts CROSS JOIN files f ON f.id = files_fts.rowid
+             WHERE {}
+             ORDER BY files_fts.rank
+             LIMIT 50",
+            conditions.join(" AND ")
+        )
+    } else {
+        // Snippets and ranking still come from any words searched for as
+        // alternatives, e.g. `rust OR tag:lang`
+        let mut highlight = Vec::new();
+        for term in &rest {
+            collect_text(term, &mut highlight);
+        }
+        let (join, snippet, order) = if highlight.is_empty() {
+            (String::new(), "substr(f.content, 1, 200)", "f.modified DESC")
+        } else {
+            let param = compiler.bind(Value::Text(highlight.join(" OR ")));
+            (
+                format!(
+                    "LEFT JOIN (SELECT rowid AS id, rank,
+                                       snippet(files_fts, 2, '<mark>', '</mark>', '...', 32) AS snippet
+                                FROM files_fts WHERE files_fts MATCH ?{}) m ON m.id = f.id",
+                    param
+                ),
+                "COALESCE(m.snippet, substr(f.content, 1, 200))",
+                "m.rank IS NULL, m.rank, f.modified DESC",
+            )
+        };
+        format!(
+            "SELECT f.path, f.title, {}
+             FROM files f {}
+             WHERE {}
+             ORDER BY {}
+             LIMIT 50",
+            snippet,
+            join,
+            conditions.join(" AND "),
+            order
+        )
+    };
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let matches = stmt
+        .query_map(params_from_iter(compiler.values.iter()), |row| {
+            Ok(ContentMatch {
+                path: row.get(0)?,
+                title: row.get(1)?,
+                snippet: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                matches: 1,
+            })
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|m| m.ok())
+        .collect();
+
+    Ok(matches)
+}
+
+impl Compiler {
+    fn bind(&mut self, value: Value) -> usize {
+        self.values.push(value);
+        self.values.len()
+    }
+
+    /// SQL condition on `files f`. Text terms become FTS queries; runs of
+    /// them joined by AND/OR are sent to FTS as one query.
+    fn condition(&mut self, expr: &SearchExpr) -> String {
+        if let Some(fts) = fts(expr) {
+            let param = self.bind(Value::Text(fts));
+            return format!(
+                "f.id IN (SELECT rowid FROM files_fts WHERE files_fts MATCH ?{})",
+                param
+            );
+        }
+
+        match expr {
+            SearchExpr::Path(path) => {
+                let pattern = format!("%{}%", escape_like(&path.to_lowercase()));
+                let param = self.bind(Value::Text(pattern));
+                format!("LOWER(f.path) LIKE ?{} ESCAPE '\\'", param)
+            }
+            SearchExpr::Tag(tag) => {
+                let param = self.bind(Value::Text(tags::tag_key(tag)));
+                tags::filter_clause(param)
+            }
+            SearchExpr::Modified(op, date) => {
+                let op = match op {
+                    CompareOp::Eq => "=",
+                    CompareOp::Ne => "!=",
+                    CompareOp::Lt => "<",
+                    CompareOp::Le => "<=",
+                    CompareOp::Gt => ">",
+                    CompareOp::Ge => ">=",
+                };
+                let param = self.bind(Value::Text(date.format("%Y-%m-%d").to_string()));
+                format!("date(f.modified, 'unixepoch', 'localtime') {} ?{}", op, param)
+            }
+            // Every condition binds tighter than NOT, and groups come back
+            // bracketed; extra brackets only count towards SQLite's depth limit
+            SearchExpr::Not(inner) => format!("NOT {}", self.condition(inner)),
+            SearchExpr::And(items) => self.join(items, " AND "),
+            SearchExpr::Or(items) => self.join(items, " OR "),
+            // Always handled as FTS above
+            SearchExpr::Text { .. } | SearchExpr::Title { .. } => unreachable!(),
+        }
+    }
+
+    fn join(&mut self, items: &[SearchExpr], separator: &str) -> String {
+        let conditions: Vec<String> = items.iter().map(|item| self.condition(item)).collect();
+        format!("({})", conditions.join(separator))
+    }
+}
+
+/// The FTS5 query for `expr`, if it is made only of text and title terms.
+fn fts(expr: &SearchExpr) -> Option<String> {
+    match expr {
+        // Paths have their own filter; matching words in them is mostly noise
+        SearchExpr::Text { text, prefix } => Some(format!("{{title content}} : {}", phrase(text, *prefix))),
+        SearchExpr::Title { text, prefix } => Some(format!("title : {}", phrase(text, *prefix))),
+        SearchExpr::And(items) => fts_join(items, " AND "),
+        SearchExpr::Or(items) => fts_join(items, " OR "),
+        _ => None,
+    }
+}
+
+fn fts_join(items: &[SearchExpr], separator: &str) -> Option<String> {
+    let parts = items.iter().map(fts).collect::<Option<Vec<_>>>()?;
+    Some(format!("({})", parts.join(separator)))
+}
+
+/// Quotes text as an FTS5 string, so operators and punctuation in it are
+/// only ever searched for, never interpreted.
+fn phrase(text: &str, prefix: bool) -> String {
+    let quoted = format!("\"{}\"", text.replace('"', "\"\""));
+    if prefix {
+        format!("{} *", quoted)
+    } else {
+        quoted
+    }
+}
+
+/// Text terms that aren't negated.
+fn collect_text(expr: &SearchExpr, out: &mut Vec<String>) {
+    match expr {
+        SearchExpr::Text { .. } => out.extend(fts(expr)),
+        SearchExpr::And(items) | SearchExpr::Or(items) => {
+            for item in items {
+                collect_text(item, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
@@ -0,0 +1,295 @@
+use crate::services::query_parser::CompareOp;
+use crate::services::task_parser;
+use chrono::NaiveDate;
+
+/// A parsed content search, e.g.
+/// `"weekly review" -draft tag:work modified:>2026-01-01 OR title:retro*`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchExpr {
+    /// A word or quoted phrase in the note text; `prefix` for a trailing `*`
+    Text { text: String, prefix: bool },
+    /// `title:` words or phrase
+    Title { text: String, prefix: bool },
+    /// `path:` substring, case-insensitive
+    Path(String),
+    /// `tag:`, including nested tags
+    Tag(String),
+    /// `modified:` date, compared by local day
+    Modified(CompareOp, NaiveDate),
+    Not(Box<SearchExpr>),
+    And(Vec<SearchExpr>),
+    Or(Vec<SearchExpr>),
+}
+
+/// Deepest bracket nesting a search keeps; brackets past it are read as plain
+/// text. Parsing and compiling recurse once per level, and SQLite only parses
+/// conditions nested a few dozen brackets deep.
+const MAX_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Term(SearchExpr),
+    /// A `-` directly before a term or group
+    Not,
+    Or,
+    Open,
+    Close,
+}
+
+/// Parses a search typed by the user. Anything that isn't valid syntax is
+/// searched for as text rather than rejected, so stray quotes, brackets and
+/// symbols never fail a search; only an unreadable `modified:` date does.
+/// Returns `None` when nothing searchable is left.
+pub fn parse(source: &str, today: NaiveDate) -> Result<Option<SearchExpr>, String> {
+    let mut tokens = tokenize(source, today)?;
+    flatten_deep_groups(&mut tokens);
+    let mut parser = Parser { tokens, pos: 0 };
+
+    let mut groups = Vec::new();
+    while parser.pos < parser.tokens.len() {
+        groups.extend(parser.or());
+        // A `)` without its `(`
+        parser.pos += 1;
+    }
+    Ok(all(groups))
+}
+
+fn tokenize(source: &str, today: NaiveDate) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    loop {
+        rest = rest.trim_start();
+        let Some(c) = rest.chars().next() else {
+            break;
+        };
+
+        match c {
+            '(' => {
+                tokens.push(Token::Open);
+                rest = &rest[1..];
+                continue;
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                rest = &rest[1..];
+                continue;
+            }
+            '-' if rest[1..].starts_with(|c: char| !c.is_whitespace() && c != '-') => {
+                tokens.push(Token::Not);
+                rest = &rest[1..];
+                continue;
+            }
+            _ => {}
+        }
+
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let (text, after) = quoted_value(quoted);
+            let (prefix, after) = prefix_star(after);
+            tokens.extend(text_term(text, prefix).map(Token::Term));
+            rest = after;
+            continue;
+        }
+
+        let field = rest
+            .split_once(':')
+            .map(|(name, _)| name)
+            .filter(|name| name.chars().all(char::is_alphabetic))
+            .map(str::to_lowercase)
+            .filter(|name| matches!(name.as_str(), "path" | "title" | "tag" | "modified"));
+        if let Some(field) = field {
+            let mut value_start = &rest[field.len() + 1..];
+            // The comparison may sit outside the quotes: `modified:>"3 days ago"`
+            let mut operator = "";
+            if field == "modified" {
+                let len = value_start.len() - value_start.trim_start_matches(['<', '>', '=']).len();
+                (operator, value_start) = value_start.split_at(len);
+            }
+            let (value, after) = match value_start.strip_prefix('"') {
+                Some(quoted) => quoted_value(quoted),
+                None => bare_value(value_start),
+            };
+            rest = after;
+            let term = match field.as_str() {
+                "path" => Some(SearchExpr::Path(value.to_string())).filter(|_| !value.is_empty()),
+                "tag" => {
+                    let tag = value.trim_start_matches('#');
+                    Some(SearchExpr::Tag(tag.to_string())).filter(|_| !tag.is_empty())
+                }
+                "title" => {
+                    let (text, prefix) = match value.strip_suffix('*') {
+                        Some(text) => (text, true),
+                        None => (value, false),
+                    };
+                    searchable(text).then(|| SearchExpr::Title {
+                        text: text.to_string(),
+                        prefix,
+                    })
+                }
+                // Still being typed
+                _ if value.is_empty() => None,
+                _ => Some(modified(operator, value, today)?),
+            };
+            tokens.extend(term.map(Token::Term));
+            continue;
+        }
+
+        // Never empty: `rest` starts with something other than a space, bracket or quote
+        let (word, after) = bare_value(rest);
+        rest = after;
+
+        if word == "OR" {
+            tokens.push(Token::Or);
+            continue;
+        }
+        let (text, prefix) = match word.strip_suffix('*') {
+            Some(text) => (text, true),
+            None => (word, false),
+        };
+        tokens.extend(text_term(text, prefix).map(Token::Term));
+    }
+
+    Ok(tokens)
+}
+
+/// Drops brackets nested deeper than `MAX_DEPTH`, with their closing
+/// brackets and any `-` inside them; read as text, neither has anything
+/// searchable in it.
+fn flatten_deep_groups(tokens: &mut Vec<Token>) {
+    let mut depth = 0;
+    let mut excess = 0;
+    tokens.retain(|token| match token {
+        Token::Open if depth == MAX_DEPTH => {
+            excess += 1;
+            false
+        }
+        Token::Open => {
+            depth += 1;
+            true
+        }
+        Token::Not => excess == 0,
+        Token::Close if excess > 0 => {
+            excess -= 1;
+            false
+        }
+        Token::Close => {
+            depth = depth.saturating_sub(1);
+            true
+        }
+        _ => true,
+    });
+}
+
+/// The text up to the closing quote, or to the end if there is none.
+fn quoted_value(text: &str) -> (&str, &str) {
+    match text.find('"') {
+        Some(end) => (&text[..end], &text[end + 1..]),
+        None => (text, ""),
+    }
+}
+
+/// The text up to whitespace, a bracket or a quote.
+fn bare_value(text: &str) -> (&str, &str) {
+    let end = text
+        .find(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '"'))
+        .unwrap_or(text.len());
+    (&text[..end], &text[end..])
+}
+
+fn prefix_star(text: &str) -> (bool, &str) {
+    match text.strip_prefix('*') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    }
+}
+
+fn text_term(text: &str, prefix: bool) -> Option<SearchExpr> {
+    searchable(text).then(|| SearchExpr::Text {
+        text: text.to_string(),
+        prefix,
+    })
+}
+
+/// Full-text search only indexes letters and digits; terms without any
+/// (`++`, `-`) can't match anything and are dropped.
+fn searchable(text: &str) -> bool {
+    text.chars().any(char::is_alphanumeric)
+}
+
+/// `modified:2026-01-01`, `modified:>=yesterday`, `modified:<"2 weeks ago"`.
+fn modified(operator: &str, date: &str, today: NaiveDate) -> Result<SearchExpr, String> {
+    let op = match operator {
+        "" | "=" => CompareOp::Eq,
+        ">" => CompareOp::Gt,
+        ">=" => CompareOp::Ge,
+        "<" => CompareOp::Lt,
+        "<=" => CompareOp::Le,
+        _ => return Err(format!("Invalid comparison: {}", operator)),
+    };
+    task_parser::relative_date(date, today)
+        .map(|date| SearchExpr::Modified(op, date))
+        .ok_or_else(|| format!("Invalid date: {}", date))
+}
+
+fn all(mut exprs: Vec<SearchExpr>) -> Option<SearchExpr> {
+    match exprs.len() {
+        0 => None,
+        1 => exprs.pop(),
+        _ => Some(SearchExpr::And(exprs)),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    /// Terms joined by `OR`, up to a closing bracket or the end.
+    fn or(&mut self) -> Option<SearchExpr> {
+        let mut alternatives = Vec::new();
+        loop {
+            alternatives.extend(self.and());
+            if self.tokens.get(self.pos) != Some(&Token::Or) {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        match alternatives.len() {
+            0 => None,
+            1 => alternatives.pop(),
+            _ => Some(SearchExpr::Or(alternatives)),
+        }
+    }
+
+    /// Adjacent terms, which must all match.
+    fn and(&mut self) -> Option<SearchExpr> {
+        let mut terms = Vec::new();
+        while let Some(token) = self.tokens.get(self.pos) {
+            if matches!(token, Token::Or | Token::Close) {
+                break;
+            }
+            terms.extend(self.unary());
+        }
+        all(terms)
+    }
+
+    fn unary(&mut self) -> Option<SearchExpr> {
+        let token = self.tokens.get(self.pos).cloned()?;
+        self.pos += 1;
+        match token {
+            Token::Not => self.unary().map(|expr| SearchExpr::Not(Box::new(expr))),
+            Token::Open => {
+                let group = self.or();
+                // An unclosed `(` runs to the end
+                if self.tokens.get(self.pos) == Some(&Token::Close) {
+                    self.pos += 1;
+                }
+                group
+            }
+            Token::Term(expr) => Some(expr),
+            Token::Or | Token::Close => None,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::models::note::{NoteRef, TagNode};
+use crate::services::note_parser::ParsedTag;
+use rusqlite::{params, Connection};
+use std::collections::{BTreeMap, HashSet};
+
+/// Replaces the stored tags of a file.
+pub fn replace(conn: &Connection, file_id: i64, tags: &[ParsedTag]) -> Result<(), String> {
+    conn.execute("DELETE FROM tags WHERE file_id = ?1", params![file_id])
+        .map_err(|e| format!("Failed to clear tags: {}", e))?;
+
+    let mut stmt = conn
+        .prepare_cached("INSERT INTO tags (file_id, tag, tag_key, line) VALUES (?1, ?2, ?3, ?4)")
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    for tag in tags {
+        stmt.execute(params![file_id, tag.tag, tag_key(&tag.tag), tag.line.map(|l| l as i64)])
+            .map_err(|e| format!("Failed to store tag: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Tags are matched case-insensitively, like in Obsidian.
+pub fn tag_key(tag: &str) -> String {
+    tag.trim_start_matches('#').to_lowercase()
+}
+
+/// SQL condition matching `files f` tagged with `?n` or a tag nested under it,
+/// where the parameter is a tag key.
+pub fn filter_clause(param: usize) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM tags t WHERE t.file_id = f.id
+             AND (t.tag_key = ?{0} OR substr(t.tag_key, 1, length(?{0}) + 1) = ?{0} || '/'))",
+        param
+    )
+}
+
+/// The tag hierarchy with per-tag file counts, sorted by name.
+pub fn list(conn: &Connection) -> Result<Vec<TagNode>, String> {
+    // The first spelling seen stands for all case variants
+    let mut stmt = conn
+        .prepare("SELECT tag_key, MIN(tag), file_id FROM tags GROUP BY tag_key, file_id")
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok());
+
+    let mut root = Builder::default();
+    for (key, tag, file_id) in rows {
+        let segments: Vec<&str> = tag.split('/').collect();
+
+        let mut node = &mut root;
+        for (depth, key_segment) in key.split('/').enumerate() {
+            node = node
+                .children
+                .entry(key_segment.to_string())
+                .or_insert_with(|| Builder {
+                    name: segments[depth].to_string(),
+                    tag: segments[..=depth].join("/"),
+                    ..Default::default()
+                });
+            node.subtree_files.insert(file_id);
+        }
+        node.files.insert(file_id);
+    }
+
+    Ok(root.into_nodes())
+}
+
+#[derive(Default)]
+struct Builder {
+    name: String,
+    tag: String,
+    files: HashSet<i64>,
+    subtree_files: HashSet<i64>,
+    children: BTreeMap<String, Builder>,
+}
+
+impl Builder {
+    fn into_nodes(self) -> Vec<TagNode> {
+        self.children
+            .into_values()
+            .map(|child| TagNode {
+                name: child.name,
+                tag: child.tag,
+                count: child.files.len() as i64,
+                total_count: child.subtree_files.len() as i64,
+                children: Builder {
+                    children: child.children,
+                    ..Default::default()
+                }
+                .into_nodes(),
+            })
+            .collect()
+    }
+}
+
+/// Files tagged with `tag`, optionally including nested tags.
+pub fn files_by_tag(conn: &Connection, tag: &str, include_nested: bool) -> Result<Vec<NoteRef>, String> {
+    let condition = if include_nested {
+        filter_clause(1)
+    } else {
+        "EXISTS (SELECT 1 FROM tags t WHERE t.file_id = f.id AND t.tag_key = ?1)".to_string()
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT f.path, f.title FROM files f WHERE {} ORDER BY f.path",
+            condition
+        ))
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let files = stmt
+        .query_map(params![tag_key(tag)], |row| {
+            Ok(NoteRef {
+                path: row.get(0)?,
+                title: row.get(1)?,
+            })
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|f| f.ok())
+        .collect();
+
+    Ok(files)
+}
@@ -0,0 +1,598 @@
+use chrono::{Days, Months, NaiveDate};
+use std::fmt;
+
+/// A `- [ ]` checklist item.
+#[derive(Debug)]
+pub struct ParsedTask {
+    pub line: usize,
+    /// The character between the brackets: ` ` open, `x` done, anything else
+    /// a custom status such as `-` cancelled or `/` in progress
+    pub status: char,
+    /// The item text with its metadata taken out
+    pub text: String,
+    /// Nearest heading above the item
+    pub heading: Option<String>,
+    pub fields: TaskFields,
+}
+
+/// Metadata written inline, either as Tasks-plugin emoji (`📅 2026-10-20`,
+/// `⏫`) or as Dataview fields (`[due:: 2026-10-20]`, `priority:: high`).
+/// Dates are kept as `YYYY-MM-DD`; unparseable dates are dropped.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TaskFields {
+    pub due: Option<String>,
+    pub scheduled: Option<String>,
+    pub start: Option<String>,
+    pub created: Option<String>,
+    /// Completion date
+    pub done: Option<String>,
+    /// `highest`, `high`, `medium`, `low` or `lowest`
+    pub priority: Option<String>,
+    /// Recurrence rule in its normalized form, e.g. `every 2 weeks when done`
+    pub recurrence: Option<String>,
+}
+
+/// Where the checkbox of a task line is.
+pub struct TaskMarker {
+    pub status: char,
+    /// Byte offset of the status character within the line
+    pub status_offset: usize,
+    /// Byte offset where the item text starts
+    pub text_offset: usize,
+}
+
+const DATE_EMOJI: [(&str, DateField); 7] = [
+    ("📅", DateField::Due),
+    ("📆", DateField::Due),
+    ("🗓", DateField::Due),
+    ("⏳", DateField::Scheduled),
+    ("🛫", DateField::Start),
+    ("➕", DateField::Created),
+    ("✅", DateField::Done),
+];
+
+const PRIORITY_EMOJI: [(&str, &str); 5] = [
+    ("🔺", "highest"),
+    ("⏫", "high"),
+    ("🔼", "medium"),
+    ("🔽", "low"),
+    ("⏬", "lowest"),
+];
+
+const RECURRENCE_EMOJI: &str = "🔁";
+
+#[derive(Clone, Copy, PartialEq)]
+enum DateField {
+    Due,
+    Scheduled,
+    Start,
+    Created,
+    Done,
+}
+
+/// Recognises `- [ ] text`, `* [x] text`, `1. [ ] text` and the like.
+pub fn marker(line: &str) -> Option<TaskMarker> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+
+    let bullet = if rest.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 || !rest[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        digits + 1
+    };
+
+    let after_bullet = &rest[bullet..];
+    let spaces = after_bullet.len() - after_bullet.trim_start_matches([' ', '\t']).len();
+    if spaces == 0 {
+        return None;
+    }
+
+    let checkbox = &after_bullet[spaces..];
+    let mut chars = checkbox.chars();
+    if chars.next() != Some('[') {
+        return None;
+    }
+    let status = chars.next()?;
+    if chars.next() != Some(']') {
+        return None;
+    }
+    let after = &checkbox[2 + status.len_utf8()..];
+    if !after.is_empty() && !after.starts_with([' ', '\t']) {
+        return None;
+    }
+
+    let status_offset = indent + bullet + spaces + 1;
+    Some(TaskMarker {
+        status,
+        status_offset,
+        text_offset: status_offset + status.len_utf8() + 1,
+    })
+}
+
+/// Where the metadata of a task's text is.
+struct Scan {
+    fields: TaskFields,
+    /// Byte ranges of the metadata, for leaving it out of the description
+    removed: Vec<(usize, usize)>,
+    /// Byte offsets of the `YYYY-MM-DD` values, for rewriting dates in place
+    dates: Vec<(DateField, usize)>,
+}
+
+/// Splits the text of a task into its description and metadata.
+pub fn fields(text: &str) -> (String, TaskFields) {
+    let Scan { fields, mut removed, .. } = scan(text);
+
+    removed.sort();
+    let mut description = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for (start, end) in removed {
+        if start >= cursor {
+            description.push_str(&text[cursor..start]);
+            cursor = end;
+        }
+    }
+    description.push_str(&text[cursor..]);
+
+    let mut words: Vec<&str> = description.split_whitespace().collect();
+    // A trailing `^block-id` labels the item, it isn't part of the text
+    if words.last().is_some_and(|word| word.len() > 1 && word.starts_with('^')) {
+        words.pop();
+    }
+    (words.join(" "), fields)
+}
+
+fn scan(text: &str) -> Scan {
+    let mut scan = Scan {
+        fields: TaskFields::default(),
+        removed: Vec::new(),
+        dates: Vec::new(),
+    };
+
+    for field in inline_fields(text) {
+        if let Some(date_field) = date_field(&field.key) {
+            if let Some(date) = parse_date(&field.value) {
+                set_date(&mut scan.fields, date_field, date);
+                scan.dates.push((date_field, field.value_start));
+            }
+        } else if field.key == "priority" {
+            let priority = field.value.to_lowercase();
+            if PRIORITY_EMOJI.iter().any(|(_, p)| *p == priority) {
+                scan.fields.priority.get_or_insert(priority);
+            }
+        } else if field.key == "repeat" || field.key == "recurrence" {
+            if let Some(rule) = Recurrence::parse(&field.value) {
+                scan.fields.recurrence.get_or_insert(rule.to_string());
+            }
+        } else {
+            continue;
+        }
+        scan.removed.push((field.start, field.end));
+    }
+
+    for (emoji, field) in DATE_EMOJI {
+        for (start, _) in text.match_indices(emoji) {
+            if overlaps(&scan.removed, start) {
+                continue;
+            }
+            let value_start = value_start(text, start + emoji.len());
+            let Some(date) = text.get(value_start..value_start + 10).and_then(parse_date) else {
+                continue;
+            };
+            set_date(&mut scan.fields, field, date);
+            scan.dates.push((field, value_start));
+            scan.removed.push((start, value_start + 10));
+        }
+    }
+
+    for (start, _) in text.match_indices(RECURRENCE_EMOJI) {
+        if overlaps(&scan.removed, start) {
+            continue;
+        }
+        // The rule runs up to the next piece of metadata
+        let value_start = value_start(text, start + RECURRENCE_EMOJI.len());
+        let value_end = text[value_start..]
+            .char_indices()
+            .find(|&(i, c)| {
+                matches!(c, '[' | '(' | '#')
+                    || text[value_start + i..].starts_with(" ^")
+                    || DATE_EMOJI.iter().any(|(emoji, _)| text[value_start + i..].starts_with(emoji))
+                    || PRIORITY_EMOJI.iter().any(|(emoji, _)| text[value_start + i..].starts_with(emoji))
+            })
+            .map_or(text.len(), |(i, _)| value_start + i);
+        if let Some(rule) = Recurrence::parse(&text[value_start..value_end]) {
+            scan.fields.recurrence.get_or_insert(rule.to_string());
+            scan.removed.push((start, value_end));
+        }
+    }
+
+    for (emoji, priority) in PRIORITY_EMOJI {
+        if let Some(start) = text.find(emoji) {
+            scan.fields.priority.get_or_insert_with(|| priority.to_string());
+            let end = start + emoji.len();
+            let end = if text[end..].starts_with('\u{fe0f}') { end + 3 } else { end };
+            scan.removed.push((start, end));
+        }
+    }
+
+    scan
+}
+
+/// Start of the value following an emoji that ends at `after_emoji`,
+/// skipping the emoji variation selector and spaces.
+fn value_start(text: &str, after_emoji: usize) -> usize {
+    let after = &text[after_emoji..];
+    let after = after.strip_prefix('\u{fe0f}').unwrap_or(after);
+    text.len() - after.trim_start().len()
+}
+
+struct InlineField {
+    start: usize,
+    end: usize,
+    key: String,
+    value: String,
+    /// Byte offset of `value` in the text
+    value_start: usize,
+}
+
+/// `[key:: value]` and `(key:: value)` anywhere in the text, and a bare
+/// `key:: value`.
+fn inline_fields(text: &str) -> Vec<InlineField> {
+    let mut found: Vec<InlineField> = Vec::new();
+
+    let mut pos = 0;
+    while let Some(offset) = text[pos..].find(['[', '(']) {
+        let start = pos + offset;
+        let close = if text[start..].starts_with('[') { ']' } else { ')' };
+        pos = start + 1;
+        let Some(len) = text[start + 1..].find(close) else {
+            continue;
+        };
+        let end = start + len + 2;
+        if let Some(colons) = text[start + 1..end - 1].find("::") {
+            let colons = start + 1 + colons;
+            let key = &text[start + 1..colons];
+            if is_field_key(key) {
+                found.push(field_at(text, start, end, key, colons));
+                pos = end;
+            }
+        }
+    }
+
+    // A bare field runs up to the next bracketed one or the end of the text
+    let bare = text
+        .match_indices("::")
+        .map(|(colons, _)| colons)
+        .find(|&colons| !found.iter().any(|f| f.start <= colons && colons < f.end));
+    if let Some(colons) = bare {
+        let key_start = text[..colons]
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .map_or(0, |i| i + text[i..].chars().next().map_or(1, char::len_utf8));
+        let end = found
+            .iter()
+            .map(|f| f.start)
+            .find(|&start| start > colons)
+            .unwrap_or(text.len());
+        let key = &text[key_start..colons];
+        if is_field_key(key) {
+            let field = field_at(text, key_start, end, key, colons);
+            found.push(field);
+        }
+    }
+
+    found
+}
+
+/// A field spanning `start..end` whose `::` is at `colons`; a closing
+/// bracket at `end - 1` isn't part of the value.
+fn field_at(text: &str, start: usize, end: usize, key: &str, colons: usize) -> InlineField {
+    let raw = text[colons + 2..end].trim_end_matches([']', ')']);
+    let value = raw.trim();
+    InlineField {
+        start,
+        end,
+        key: key.trim().to_lowercase(),
+        value: value.to_string(),
+        value_start: colons + 2 + (raw.len() - raw.trim_start().len()),
+    }
+}
+
+fn is_field_key(key: &str) -> bool {
+    let key = key.trim();
+    !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == ' ')
+}
+
+fn date_field(key: &str) -> Option<DateField> {
+    match key {
+        "due" => Some(DateField::Due),
+        "scheduled" => Some(DateField::Scheduled),
+        "start" => Some(DateField::Start),
+        "created" => Some(DateField::Created),
+        "completion" | "completed" | "done" => Some(DateField::Done),
+        _ => None,
+    }
+}
+
+fn set_date(fields: &mut TaskFields, field: DateField, date: String) {
+    let slot = match field {
+        DateField::Due => &mut fields.due,
+        DateField::Scheduled => &mut fields.scheduled,
+        DateField::Start => &mut fields.start,
+        DateField::Created => &mut fields.created,
+        DateField::Done => &mut fields.done,
+    };
+    slot.get_or_insert(date);
+}
+
+fn parse_date(value: &str) -> Option<String> {
+    let value = value.trim();
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d")
+        .ok()
+        .map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+fn overlaps(ranges: &[(usize, usize)], at: usize) -> bool {
+    ranges.iter().any(|&(start, end)| start <= at && at < end)
+}
+
+/// Result of toggling a task line.
+pub struct Toggled {
+    pub line: String,
+    /// The next occurrence of a recurring task that was just completed
+    pub next: Option<String>,
+}
+
+/// The task line with its checkbox flipped: done items reopen, anything else
+/// is completed. The completion date is written (or removed) in whichever
+/// metadata style the line already uses; plain items just get the checkbox.
+pub fn toggle(line: &str, today: NaiveDate) -> Option<Toggled> {
+    let marker = marker(line)?;
+    let completing = !matches!(marker.status, 'x' | 'X');
+
+    let mut text = line[marker.text_offset..].to_string();
+    remove_done(&mut text);
+    let next = if completing { next_occurrence(line, &marker, &text, today) } else { None };
+
+    if completing {
+        let date = today.format("%Y-%m-%d");
+        let uses_emoji = DATE_EMOJI
+            .iter()
+            .map(|(emoji, _)| *emoji)
+            .chain(PRIORITY_EMOJI.iter().map(|(emoji, _)| *emoji))
+            .chain([RECURRENCE_EMOJI])
+            .any(|emoji| text.contains(emoji));
+        let done = if uses_emoji {
+            Some(format!("✅ {}", date))
+        } else if text.contains("::") {
+            Some(format!("[completion:: {}]", date))
+        } else {
+            None
+        };
+        if let Some(done) = done {
+            insert_before_anchor(&mut text, &done);
+        }
+    }
+
+    let status = if completing { 'x' } else { ' ' };
+    Some(Toggled {
+        line: format!("{}{}]{}", &line[..marker.status_offset], status, text),
+        next,
+    })
+}
+
+/// For a recurring task, the open copy to add once it's completed: due,
+/// scheduled and start dates move on by the rule's interval, counted from
+/// the due date (or, failing that, the scheduled or start date), or from
+/// `today` for `when done` rules. A created date becomes `today`; the block
+/// anchor stays with the original.
+fn next_occurrence(line: &str, marker: &TaskMarker, text: &str, today: NaiveDate) -> Option<String> {
+    let scan = scan(text);
+    let rule = Recurrence::parse(scan.fields.recurrence.as_deref()?)?;
+
+    let reference = [&scan.fields.due, &scan.fields.scheduled, &scan.fields.start]
+        .into_iter()
+        .find_map(|date| date.as_deref().and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()));
+    let next_reference = match (rule.when_done, reference) {
+        (false, Some(reference)) => rule.interval.add(reference, 1)?,
+        _ => rule.interval.add(today, 1)?,
+    };
+    let shift = reference.map(|reference| next_reference - reference);
+
+    let mut next = text.to_string();
+    let mut dates = scan.dates;
+    dates.sort_by_key(|&(_, offset)| std::cmp::Reverse(offset));
+    for (field, offset) in dates {
+        let replacement = match field {
+            DateField::Due | DateField::Scheduled | DateField::Start => {
+                let date = NaiveDate::parse_from_str(&next[offset..offset + 10], "%Y-%m-%d").ok()?;
+                date.checked_add_signed(shift?)?
+            }
+            DateField::Created => today,
+            DateField::Done => continue,
+        };
+        next.replace_range(offset..offset + 10, &replacement.format("%Y-%m-%d").to_string());
+    }
+
+    let trimmed = next.trim_end();
+    if let Some((before, last)) = trimmed.rsplit_once(' ') {
+        if last.len() > 1 && last.starts_with('^') {
+            next.truncate(before.len());
+        }
+    }
+
+    Some(format!("{} ]{}", &line[..marker.status_offset], next))
+}
+
+/// Drops `✅ date` and `[completion:: date]` from task text, along with the
+/// space in front of them.
+fn remove_done(text: &mut String) {
+    let mut ranges: Vec<(usize, usize)> = inline_fields(text)
+        .into_iter()
+        .filter(|field| date_field(&field.key) == Some(DateField::Done))
+        .map(|field| (field.start, field.end))
+        .collect();
+
+    for (start, _) in text.match_indices("✅") {
+        let value_start = value_start(text, start + "✅".len());
+        if text.get(value_start..value_start + 10).and_then(parse_date).is_some() {
+            ranges.push((start, value_start + 10));
+        }
+    }
+
+    ranges.sort();
+    for (start, end) in ranges.into_iter().rev() {
+        let start = if text[..start].ends_with(' ') { start - 1 } else { start };
+        text.replace_range(start..end, "");
+    }
+}
+
+/// Appends `field`, but ahead of a trailing `^block-id` so the anchor stays last.
+fn insert_before_anchor(text: &mut String, field: &str) {
+    let trimmed = text.trim_end();
+    let anchor_start = trimmed
+        .rsplit_once(' ')
+        .filter(|(_, last)| last.starts_with('^') && last.len() > 1)
+        .map(|(before, _)| before.len());
+
+    match anchor_start {
+        Some(at) => text.insert_str(at, &format!(" {}", field)),
+        None => {
+            let end = trimmed.len();
+            text.insert_str(end, &format!(" {}", field));
+        }
+    }
+}
+
+/// A stretch of time in the grammar recurrence rules and relative dates
+/// share: `day`, `3 days`, `other week`, `2 months`, `year`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub count: u32,
+    pub unit: IntervalUnit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntervalUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Interval {
+    pub fn parse(text: &str) -> Option<Self> {
+        let words: Vec<String> = text.split_whitespace().map(|w| w.to_lowercase()).collect();
+        let (count, unit) = match words.as_slice() {
+            [unit] => (1, unit),
+            [count, unit] if count == "other" => (2, unit),
+            [count, unit] => (count.parse().ok().filter(|&n| n > 0)?, unit),
+            _ => return None,
+        };
+        let unit = match unit.trim_end_matches('s') {
+            "day" => IntervalUnit::Day,
+            "week" => IntervalUnit::Week,
+            "month" => IntervalUnit::Month,
+            "year" => IntervalUnit::Year,
+            _ => return None,
+        };
+        Some(Self { count, unit })
+    }
+
+    /// `date` moved by `times` intervals (backwards when negative). Months
+    /// that are too short clamp to their last day.
+    pub fn add(&self, date: NaiveDate, times: i32) -> Option<NaiveDate> {
+        let steps = self.count.checked_mul(times.unsigned_abs())?;
+        let forward = times >= 0;
+        match self.unit {
+            IntervalUnit::Day | IntervalUnit::Week => {
+                let days = Days::new(u64::from(steps) * if self.unit == IntervalUnit::Week { 7 } else { 1 });
+                if forward { date.checked_add_days(days) } else { date.checked_sub_days(days) }
+            }
+            IntervalUnit::Month | IntervalUnit::Year => {
+                let months = Months::new(steps.checked_mul(if self.unit == IntervalUnit::Year { 12 } else { 1 })?);
+                if forward { date.checked_add_months(months) } else { date.checked_sub_months(months) }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.unit {
+            IntervalUnit::Day => "day",
+            IntervalUnit::Week => "week",
+            IntervalUnit::Month => "month",
+            IntervalUnit::Year => "year",
+        };
+        if self.count == 1 {
+            write!(f, "{}", unit)
+        } else {
+            write!(f, "{} {}s", self.count, unit)
+        }
+    }
+}
+
+/// `every <interval>` or `daily` / `weekly` / `monthly` / `yearly`,
+/// optionally followed by `when done` to count from the completion date.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Recurrence {
+    pub interval: Interval,
+    pub when_done: bool,
+}
+
+impl Recurrence {
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().to_lowercase();
+        let (rule, when_done) = match text.strip_suffix("when done") {
+            Some(rule) => (rule.trim_end().trim_end_matches(',').trim_end(), true),
+            None => (text.as_str(), false),
+        };
+
+        let unit = match rule {
+            "daily" => Some(IntervalUnit::Day),
+            "weekly" => Some(IntervalUnit::Week),
+            "monthly" => Some(IntervalUnit::Month),
+            "yearly" | "annually" => Some(IntervalUnit::Year),
+            _ => None,
+        };
+        let interval = match unit {
+            Some(unit) => Interval { count: 1, unit },
+            None => Interval::parse(rule.strip_prefix("every ")?)?,
+        };
+
+        Some(Self { interval, when_done })
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "every {}", self.interval)?;
+        if self.when_done {
+            write!(f, " when done")?;
+        }
+        Ok(())
+    }
+}
+
+/// A date in a task filter: `YYYY-MM-DD`, `today`, `tomorrow`, `yesterday`,
+/// `in <interval>` or `<interval> ago`.
+pub fn relative_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = text.trim().to_lowercase();
+    match text.as_str() {
+        "today" => return Some(today),
+        "tomorrow" => return today.succ_opt(),
+        "yesterday" => return today.pred_opt(),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+        return Some(date);
+    }
+    if let Some(interval) = text.strip_prefix("in ") {
+        return Interval::parse(interval)?.add(today, 1);
+    }
+    let interval = text.strip_suffix(" ago")?;
+    Interval::parse(interval)?.add(today, -1)
+}
@@ -0,0 +1,211 @@
+use crate::models::file::ModifiedFile;
+use crate::models::task::{Task, TaskFilter};
+use crate::services::file_indexer::reindex_file;
+use crate::services::links;
+use crate::services::note_parser;
+use crate::services::tags;
+use crate::services::task_parser::{self, ParsedTask};
+use chrono::{Local, NaiveDate};
+use rusqlite::{params, params_from_iter, Connection};
+use std::fs;
+use std::path::{Path, MAIN_SEPARATOR};
+
+/// Replaces the stored tasks of a file.
+pub fn replace(conn: &Connection, file_id: i64, tasks: &[ParsedTask]) -> Result<(), String> {
+    conn.execute("DELETE FROM tasks WHERE file_id = ?1", params![file_id])
+        .map_err(|e| format!("Failed to clear tasks: {}", e))?;
+
+    let mut stmt = conn
+        .prepare_cached(
+            "INSERT INTO tasks (file_id, line, status, completed, text, heading,
+                                due, scheduled, start, created, done, priority, recurrence)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    for task in tasks {
+        stmt.execute(params![
+            file_id,
+            task.line as i64,
+            task.status.to_string(),
+            matches!(task.status, 'x' | 'X'),
+            task.text,
+            task.heading,
+            task.fields.due,
+            task.fields.scheduled,
+            task.fields.start,
+            task.fields.created,
+            task.fields.done,
+            task.fields.priority,
+            task.fields.recurrence,
+        ])
+        .map_err(|e| format!("Failed to store task: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Tasks across the index matching `filter`: open ones first, then by due
+/// date (undated last), priority and position.
+pub fn query(conn: &Connection, filter: &TaskFilter) -> Result<Vec<Task>, String> {
+    let mut sql = String::from(
+        "SELECT f.path, t.line, t.status, t.completed, t.text, t.heading,
+                t.due, t.scheduled, t.start, t.created, t.done, t.priority, t.recurrence,
+                (SELECT json_group_array(tg.tag) FROM tags tg
+                 WHERE tg.file_id = t.file_id AND tg.line = t.line)
+         FROM tasks t JOIN files f ON f.id = t.file_id
+         WHERE 1 = 1",
+    );
+    let mut values: Vec<String> = Vec::new();
+
+    if let Some(completed) = filter.completed {
+        sql.push_str(if completed { " AND t.completed = 1" } else { " AND t.completed = 0" });
+    }
+    if let Some(folder) = &filter.folder {
+        values.push(format!("{}{}", folder.trim_end_matches(MAIN_SEPARATOR), MAIN_SEPARATOR));
+        sql.push_str(&format!(" AND substr(f.path, 1, length(?{0})) = ?{0}", values.len()));
+    }
+    if let Some(path) = &filter.path {
+        values.push(path.clone());
+        sql.push_str(&format!(" AND f.path = ?{}", values.len()));
+    }
+    if let Some(tag) = &filter.tag {
+        values.push(tags::tag_key(tag));
+        sql.push_str(&format!(
+            " AND EXISTS (SELECT 1 FROM tags tg WHERE tg.file_id = t.file_id AND tg.line = t.line
+                 AND (tg.tag_key = ?{0} OR substr(tg.tag_key, 1, length(?{0}) + 1) = ?{0} || '/'))",
+            values.len()
+        ));
+    }
+    if let Some(recurring) = filter.recurring {
+        sql.push_str(if recurring { " AND t.recurrence IS NOT NULL" } else { " AND t.recurrence IS NULL" });
+    }
+    let today = Local::now().date_naive();
+    if let Some(date) = &filter.due_before {
+        values.push(filter_date(date, today)?);
+        sql.push_str(&format!(" AND t.due <= ?{}", values.len()));
+    }
+    if let Some(date) = &filter.due_after {
+        values.push(filter_date(date, today)?);
+        sql.push_str(&format!(" AND t.due >= ?{}", values.len()));
+    }
+    if let Some(priority) = &filter.priority {
+        values.push(priority.to_lowercase());
+        sql.push_str(&format!(" AND t.priority = ?{}", values.len()));
+    }
+    if let Some(text) = &filter.text {
+        values.push(format!("%{}%", text.to_lowercase()));
+        sql.push_str(&format!(" AND LOWER(t.text) LIKE ?{}", values.len()));
+    }
+
+    sql.push_str(
+        " ORDER BY t.completed, t.due IS NULL, t.due,
+             CASE t.priority WHEN 'highest' THEN 0 WHEN 'high' THEN 1 WHEN 'medium' THEN 2
+                             WHEN 'low' THEN 4 WHEN 'lowest' THEN 5 ELSE 3 END,
+             f.path, t.line",
+    );
+    if let Some(limit) = filter.limit {
+        sql.push_str(&format!(" LIMIT {}", limit));
+    }
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let tasks = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            let tags: Option<String> = row.get(13)?;
+            Ok(Task {
+                path: row.get(0)?,
+                line: row.get::<_, i64>(1)? as usize,
+                status: row.get(2)?,
+                completed: row.get(3)?,
+                text: row.get(4)?,
+                heading: row.get(5)?,
+                due: row.get(6)?,
+                scheduled: row.get(7)?,
+                start: row.get(8)?,
+                created: row.get(9)?,
+                done: row.get(10)?,
+                priority: row.get(11)?,
+                recurrence: row.get(12)?,
+                tags: tags
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+            })
+        })
+        .map_err(|e| format!("Failed to execute query: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(tasks)
+}
+
+/// Completes or reopens the task on `line` of the note at `path`, then
+/// reindexes the note. Only that line is rewritten, and only if it still
+/// holds a task outside code blocks whose text is `expected_text`, so an
+/// edit made since the task was listed isn't toggled by mistake. Completing
+/// a recurring task also adds its next occurrence beneath it, after any
+/// items nested under it.
+pub fn toggle(conn: &Connection, path: &Path, line: usize, expected_text: &str) -> Result<ModifiedFile, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let body_lines = note_parser::body_lines(&content);
+    let index = body_lines
+        .iter()
+        .position(|l| l.number == line)
+        .ok_or_else(|| format!("Line {} is not a task", line))?;
+    let body_line = &body_lines[index];
+    let marker = task_parser::marker(body_line.text).ok_or_else(|| format!("Line {} is not a task", line))?;
+    let (text, _) = task_parser::fields(&body_line.text[marker.text_offset..]);
+    if text != expected_text {
+        return Err("Task is no longer on that line".to_string());
+    }
+    let toggled = task_parser::toggle(body_line.text, Local::now().date_naive())
+        .ok_or_else(|| format!("Line {} is not a task", line))?;
+
+    let mut rewritten = content.clone();
+    if let Some(next) = toggled.next {
+        let after = &content[body_line.offset + body_line.text.len()..];
+        let line_ending = if after.starts_with("\r\n") { "\r\n" } else { "\n" };
+        let indent = indent_width(body_line.text);
+        let last = body_lines[index + 1..]
+            .iter()
+            .zip(&body_lines[index..])
+            .take_while(|(l, prev)| {
+                l.number == prev.number + 1 && !l.text.trim().is_empty() && indent_width(l.text) > indent
+            })
+            .last()
+            .map_or(body_line, |(l, _)| l);
+        let end = last.offset + last.text.len();
+        rewritten.insert_str(end, &format!("{}{}", line_ending, next));
+    }
+    rewritten.replace_range(body_line.offset..body_line.offset + body_line.text.len(), &toggled.line);
+    fs::write(path, &rewritten).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    // The toggle is on disk; failing now would invite a retry that undoes
+    // it. The watcher or the next crawl picks the edit up instead
+    if let Err(e) = reindex_file(conn, path).and_then(|_| links::resolve_pending(conn)) {
+        eprintln!("Failed to index {:?}: {}", path, e);
+    }
+
+    Ok(ModifiedFile {
+        path: path.to_string_lossy().to_string(),
+        previous_content: content,
+    })
+}
+
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Filter dates may be relative (`today`, `in 2 weeks`); stored dates are
+/// compared as `YYYY-MM-DD` text.
+fn filter_date(text: &str, today: NaiveDate) -> Result<String, String> {
+    task_parser::relative_date(text, today)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| format!("Invalid date: {}", text))
+}
@@ -3,6 +3,7 @@ import { AppLayout } from './components/layout/AppLayout';
 import { useFileStore } from './stores/fileStore';
 import * as searchService from './services/searchService';
 import { listen } from '@tauri-apps/api/event';
+import { FileChangedEvent, IndexStats } from './types/search';
 
 function App() {
   const initializeWithHome = useFileStore((state) => state.initializeWithHome);
@@ -21,17 +22,25 @@ function App() {
   // Listen for indexing completion events from backend
   useEffect(() => {
     const setupListeners = async () => {
-      const unlistenComplete = await listen<number>('indexing-complete', (event) => {
-        console.log(`✅ Indexed ${event.payload} markdown files`);
+      const unlistenComplete = await listen<IndexStats>('indexing-complete', (event) => {
+        const { added, updated, removed, unchanged } = event.payload;
+        console.log(
+          `✅ Indexing complete: ${added} added, ${updated} updated, ${removed} removed, ${unchanged} unchanged`
+        );
       });
 
       const unlistenError = await listen<string>('indexing-error', (event) => {
         console.error('Indexing error:', event.payload);
       });
 
+      const unlistenChanged = await listen<FileChangedEvent>('file-changed', (event) => {
+        console.log(`Index updated (${event.payload.kind}): ${event.payload.path}`);
+      });
+
       return () => {
         unlistenComplete();
         unlistenError();
+        unlistenChanged();
       };
     };
 
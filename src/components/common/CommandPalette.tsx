@@ -113,6 +113,7 @@ export function CommandPalette({ isOpen, mode, onClose }: CommandPaletteProps) {
         path: f.path,
         title: f.name,
         score: 0,
+        matched_alias: null,
       }));
       setFileResults(matches);
       return;
@@ -259,6 +260,11 @@ export function CommandPalette({ isOpen, mode, onClose }: CommandPaletteProps) {
                     <div className="flex-1 min-w-0">
                       <div className="text-sm font-medium text-gray-900 dark:text-gray-100 truncate">
                         {(result as FileMatch).title || Path.basename((result as FileMatch).path)}
+                        {(result as FileMatch).matched_alias && (
+                          <span className="ml-2 text-xs font-normal text-gray-500 dark:text-gray-400">
+                            alias: {(result as FileMatch).matched_alias}
+                          </span>
+                        )}
                       </div>
                       <div className="text-xs text-gray-500 dark:text-gray-400 truncate">
                         {(result as FileMatch).path}
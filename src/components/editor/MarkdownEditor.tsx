@@ -33,6 +33,12 @@ const lightTheme = EditorView.theme({
   }
 }, { dark: false });
 
+// Moves the cursor to a 1-based line and scrolls it into view
+function revealLine(view: EditorView, lineNumber: number) {
+  const line = view.state.doc.line(Math.min(Math.max(lineNumber, 1), view.state.doc.lines));
+  view.dispatch({ selection: { anchor: line.from }, scrollIntoView: true });
+}
+
 interface MarkdownEditorProps {
   initialContent: string;
   filePath: string;
@@ -46,7 +52,8 @@ export function MarkdownEditor({ initialContent, filePath, onSave }: MarkdownEdi
   const updateFileContent = useFileStore((state) => state.updateFileContent);
   const selectFile = useFileStore((state) => state.selectFile);
   const files = useFileStore((state) => state.files);
-  const currentDirectory = useFileStore((state) => state.currentDirectory);
+  // Line to reveal once the next file's editor is created (link anchors)
+  const pendingLineRef = useRef<number | null>(null);
   const [isDarkMode, setIsDarkMode] = useState(() => document.documentElement.classList.contains('dark'));
 
   // Watch for dark mode changes
@@ -102,15 +109,22 @@ export function MarkdownEditor({ initialContent, filePath, onSave }: MarkdownEdi
 
       try {
         // Try to resolve the wikilink
-        const targetPath = await resolveWikilink(wikilink, currentDirectory || '');
+        const target = await resolveWikilink(wikilink, filePath);
         
-        if (targetPath) {
+        if (target) {
+          const targetLine = target.anchor?.range.start_line ?? null;
+          if (target.path === filePath) {
+            if (targetLine !== null) revealLine(view, targetLine);
+            return;
+          }
+
           // Find the file in the current files list
-          const targetFile = files.find((f) => f.path === targetPath);
+          const targetFile = files.find((f) => f.path === target.path);
           if (targetFile) {
+            pendingLineRef.current = targetLine;
             selectFile(targetFile);
           } else {
-            console.warn('File found but not in current directory:', targetPath);
+            console.warn('File found but not in current directory:', target.path);
           }
         } else {
           console.warn('Wikilink target not found:', wikilink);
@@ -165,6 +179,11 @@ export function MarkdownEditor({ initialContent, filePath, onSave }: MarkdownEdi
 
     viewRef.current = view;
 
+    if (pendingLineRef.current !== null) {
+      revealLine(view, pendingLineRef.current);
+      pendingLineRef.current = null;
+    }
+
     // Cleanup
     return () => {
       view.destroy();
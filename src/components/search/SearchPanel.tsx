@@ -146,7 +146,7 @@ export function SearchPanel({ isOpen, onClose }: SearchPanelProps) {
               placeholder={
                 activeTab === 'files'
                   ? 'Search file names...'
-                  : 'Search file contents...'
+                  : 'Search file contents ("phrase", -word, tag:, path:, modified:>date)...'
               }
               className="flex-1 bg-transparent outline-none text-gray-900 dark:text-gray-100 placeholder-gray-500"
             />
@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
-import { FileEntry } from '../types/file';
+import { FileEntry, RenameResult } from '../types/file';
 
 export async function getHomeDirectory(): Promise<string> {
   try {
@@ -9,9 +9,12 @@ export async function getHomeDirectory(): Promise<string> {
   }
 }
 
-export async function readDirectory(path: string): Promise<FileEntry[]> {
+export async function readDirectory(
+  path: string,
+  hideIgnored = false
+): Promise<FileEntry[]> {
   try {
-    return await invoke<FileEntry[]>('read_directory', { path });
+    return await invoke<FileEntry[]>('read_directory', { path, hideIgnored });
   } catch (error) {
     throw new Error(`Failed to read directory: ${error}`);
   }
@@ -49,3 +52,10 @@ export async function createDirectory(path: string): Promise<void> {
   }
 }
 
+export async function renamePath(from: string, to: string): Promise<RenameResult> {
+  try {
+    return await invoke<RenameResult>('rename_path', { from, to });
+  } catch (error) {
+    throw new Error(`Failed to rename: ${error}`);
+  }
+}
@@ -0,0 +1,10 @@
+import { invoke } from '@tauri-apps/api/core';
+import { Graph, GraphFilter } from '../types/graph';
+
+export async function getGraph(filter: GraphFilter = {}): Promise<Graph> {
+  try {
+    return await invoke<Graph>('get_graph', { filter });
+  } catch (error) {
+    throw new Error(`Failed to get graph: ${error}`);
+  }
+}
@@ -0,0 +1,113 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import { Backlink, NoteRef, Property, PropertyKey, RenderedNote, TagNode, UnlinkedMention } from '../types/note';
+import { ModifiedFile } from '../types/file';
+
+export async function getProperties(path: string): Promise<Property[]> {
+  try {
+    return await invoke<Property[]>('get_properties', { path });
+  } catch (error) {
+    throw new Error(`Failed to get properties: ${error}`);
+  }
+}
+
+export async function listPropertyKeys(): Promise<PropertyKey[]> {
+  try {
+    return await invoke<PropertyKey[]>('list_property_keys');
+  } catch (error) {
+    throw new Error(`Failed to list property keys: ${error}`);
+  }
+}
+
+export async function listTags(): Promise<TagNode[]> {
+  try {
+    return await invoke<TagNode[]>('list_tags');
+  } catch (error) {
+    throw new Error(`Failed to list tags: ${error}`);
+  }
+}
+
+export async function filesByTag(tag: string, includeNested = true): Promise<NoteRef[]> {
+  try {
+    return await invoke<NoteRef[]>('files_by_tag', { tag, includeNested });
+  } catch (error) {
+    throw new Error(`Failed to get files by tag: ${error}`);
+  }
+}
+
+export async function getBacklinks(path: string): Promise<Backlink[]> {
+  try {
+    return await invoke<Backlink[]>('get_backlinks', { path });
+  } catch (error) {
+    throw new Error(`Failed to get backlinks: ${error}`);
+  }
+}
+
+export async function getUnlinkedMentions(path: string): Promise<UnlinkedMention[]> {
+  try {
+    return await invoke<UnlinkedMention[]>('get_unlinked_mentions', { path });
+  } catch (error) {
+    throw new Error(`Failed to get unlinked mentions: ${error}`);
+  }
+}
+
+export async function linkMention(path: string, mention: UnlinkedMention): Promise<ModifiedFile> {
+  try {
+    return await invoke<ModifiedFile>('link_mention', {
+      path,
+      sourcePath: mention.source_path,
+      line: mention.line,
+      start: mention.start,
+      end: mention.end,
+    });
+  } catch (error) {
+    throw new Error(`Failed to link mention: ${error}`);
+  }
+}
+
+export async function renderNote(path: string): Promise<RenderedNote> {
+  try {
+    return await invoke<RenderedNote>('render_note', { path });
+  } catch (error) {
+    throw new Error(`Failed to render note: ${error}`);
+  }
+}
+
+/**
+ * Renders a note, then renders it again whenever the index changes in a way
+ * that could affect it: a note it embeds was edited, or it has query blocks.
+ */
+export async function watchRenderedNote(
+  path: string,
+  onUpdate: (note: RenderedNote) => void,
+  onError: (error: Error) => void = console.error
+): Promise<UnlistenFn> {
+  let current: RenderedNote | null = null;
+  const refresh = async () => {
+    try {
+      current = await renderNote(path);
+      onUpdate(current);
+    } catch (error) {
+      onError(error as Error);
+    }
+  };
+
+  const unlistenUpdated = await listen<string[]>('index-updated', (event) => {
+    const affected =
+      current === null ||
+      current.query_blocks.length > 0 ||
+      event.payload.some((changed) => current?.source_map.some((segment) => segment.path === changed));
+    if (affected) {
+      refresh();
+    }
+  });
+  const unlistenComplete = await listen('indexing-complete', () => {
+    refresh();
+  });
+  await refresh();
+
+  return () => {
+    unlistenUpdated();
+    unlistenComplete();
+  };
+}
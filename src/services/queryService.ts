@@ -0,0 +1,10 @@
+import { invoke } from '@tauri-apps/api/core';
+import { QueryResult } from '../types/query';
+
+export async function runQuery(source: string): Promise<QueryResult> {
+  try {
+    return await invoke<QueryResult>('run_query', { source });
+  } catch (error) {
+    throw new Error(`Failed to run query: ${error}`);
+  }
+}
@@ -1,5 +1,13 @@
 import { invoke } from '@tauri-apps/api/core';
-import { FileMatch, ContentMatch } from '../types/search';
+import {
+  FileMatch,
+  ContentMatch,
+  IndexJob,
+  IndexedRoot,
+  IndexCheckReport,
+  IndexRepairReport,
+} from '../types/search';
+import { LinkCandidate, WikilinkTarget } from '../types/note';
 
 export async function initIndex(): Promise<string> {
   try {
@@ -17,6 +25,82 @@ export async function notifyDirectoryOpened(directory: string): Promise<void> {
   }
 }
 
+export async function cancelIndexing(directory: string): Promise<boolean> {
+  try {
+    return await invoke<boolean>('cancel_indexing', { directory });
+  } catch (error) {
+    throw new Error(`Failed to cancel indexing: ${error}`);
+  }
+}
+
+export async function setIndexingWorkers(workers: number): Promise<void> {
+  try {
+    await invoke<void>('set_indexing_workers', { workers });
+  } catch (error) {
+    throw new Error(`Failed to set indexing workers: ${error}`);
+  }
+}
+
+export async function getIndexingQueue(): Promise<IndexJob[]> {
+  try {
+    return await invoke<IndexJob[]>('get_indexing_queue');
+  } catch (error) {
+    throw new Error(`Failed to get indexing queue: ${error}`);
+  }
+}
+
+export async function listIndexedRoots(): Promise<IndexedRoot[]> {
+  try {
+    return await invoke<IndexedRoot[]>('list_indexed_roots');
+  } catch (error) {
+    throw new Error(`Failed to list indexed roots: ${error}`);
+  }
+}
+
+export async function reindexRoot(root: string): Promise<void> {
+  try {
+    await invoke<void>('reindex_root', { root });
+  } catch (error) {
+    throw new Error(`Failed to reindex root: ${error}`);
+  }
+}
+
+export async function forgetRoot(root: string): Promise<number> {
+  try {
+    return await invoke<number>('forget_root', { root });
+  } catch (error) {
+    throw new Error(`Failed to forget root: ${error}`);
+  }
+}
+
+export async function setRootOptions(
+  root: string,
+  followSymlinks: boolean,
+  maxDepth: number | null = null
+): Promise<void> {
+  try {
+    await invoke('set_root_options', { root, followSymlinks, maxDepth });
+  } catch (error) {
+    throw new Error(`Failed to set root options: ${error}`);
+  }
+}
+
+export async function checkIndex(): Promise<IndexCheckReport> {
+  try {
+    return await invoke<IndexCheckReport>('check_index');
+  } catch (error) {
+    throw new Error(`Failed to check index: ${error}`);
+  }
+}
+
+export async function repairIndex(): Promise<IndexRepairReport> {
+  try {
+    return await invoke<IndexRepairReport>('repair_index');
+  } catch (error) {
+    throw new Error(`Failed to repair index: ${error}`);
+  }
+}
+
 export async function searchFiles(query: string): Promise<FileMatch[]> {
   try {
     return await invoke<FileMatch[]>('search_files', { query });
@@ -25,22 +109,37 @@ export async function searchFiles(query: string): Promise<FileMatch[]> {
   }
 }
 
-export async function searchContent(query: string): Promise<ContentMatch[]> {
+export async function searchContent(
+  query: string,
+  tags: string[] = []
+): Promise<ContentMatch[]> {
   try {
-    return await invoke<ContentMatch[]>('search_content', { query });
+    return await invoke<ContentMatch[]>('search_content', { query, tags });
   } catch (error) {
     throw new Error(`Failed to search content: ${error}`);
   }
 }
 
+/** `currentPath` is the note the link is written in. */
 export async function resolveWikilink(
   link: string,
-  currentDir: string
-): Promise<string | null> {
+  currentPath: string
+): Promise<WikilinkTarget | null> {
   try {
-    return await invoke<string | null>('resolve_wikilink', { link, currentDir });
+    return await invoke<WikilinkTarget | null>('resolve_wikilink', { link, currentPath });
   } catch (error) {
     throw new Error(`Failed to resolve wikilink: ${error}`);
   }
 }
 
+
+export async function resolveWikilinkCandidates(
+  link: string,
+  currentPath: string
+): Promise<LinkCandidate[]> {
+  try {
+    return await invoke<LinkCandidate[]>('resolve_wikilink_candidates', { link, currentPath });
+  } catch (error) {
+    throw new Error(`Failed to resolve wikilink candidates: ${error}`);
+  }
+}
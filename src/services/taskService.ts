@@ -0,0 +1,20 @@
+import { invoke } from '@tauri-apps/api/core';
+import { ModifiedFile } from '../types/file';
+import { Task, TaskFilter } from '../types/task';
+
+export async function queryTasks(filter: TaskFilter = {}): Promise<Task[]> {
+  try {
+    return await invoke<Task[]>('query_tasks', { filter });
+  } catch (error) {
+    throw new Error(`Failed to query tasks: ${error}`);
+  }
+}
+
+/** Fails if the note has changed so that `task` is no longer on its line. */
+export async function toggleTask(task: Task): Promise<ModifiedFile> {
+  try {
+    return await invoke<ModifiedFile>('toggle_task', { path: task.path, line: task.line, text: task.text });
+  } catch (error) {
+    throw new Error(`Failed to toggle task: ${error}`);
+  }
+}
@@ -6,3 +6,13 @@ export interface FileEntry {
   modified: string | null;
 }
 
+export interface ModifiedFile {
+  path: string;
+  previous_content: string;
+}
+
+export interface RenameResult {
+  from: string;
+  to: string;
+  modified_files: ModifiedFile[];
+}
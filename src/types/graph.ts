@@ -0,0 +1,29 @@
+export type GraphNodeKind = 'note' | 'tag' | 'attachment';
+
+export interface GraphNode {
+  id: string;
+  kind: GraphNodeKind;
+  label: string;
+  degree: number;
+  component: number;
+}
+
+export interface GraphEdge {
+  source: string;
+  target: string;
+  kind: string;
+}
+
+export interface Graph {
+  nodes: GraphNode[];
+  edges: GraphEdge[];
+}
+
+export interface GraphFilter {
+  folder?: string;
+  tag?: string;
+  center?: string;
+  depth?: number;
+  include_tags?: boolean;
+  include_attachments?: boolean;
+}
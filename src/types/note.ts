@@ -0,0 +1,98 @@
+import { QueryBlock } from './query';
+
+export type PropertyType = 'string' | 'number' | 'bool' | 'date' | 'list' | 'object';
+
+export interface Property {
+  key: string;
+  value_type: PropertyType;
+  value: unknown;
+}
+
+export interface PropertyKey {
+  key: string;
+  value_type: PropertyType;
+  file_count: number;
+}
+
+export interface NoteRef {
+  path: string;
+  title: string | null;
+}
+
+export interface TagNode {
+  name: string;
+  tag: string;
+  count: number;
+  total_count: number;
+  children: TagNode[];
+}
+
+export type LinkKind = 'wikilink' | 'embed' | 'markdown';
+
+export interface Backlink {
+  source_path: string;
+  source_title: string | null;
+  target: string;
+  kind: LinkKind;
+  line: number;
+  context: string;
+}
+
+export interface UnlinkedMention {
+  source_path: string;
+  source_title: string | null;
+  line: number;
+  /** Byte range within the line */
+  start: number;
+  end: number;
+  text: string;
+  context: string;
+}
+
+export interface SourceRange {
+  start_line: number;
+  end_line: number;
+  start_byte: number;
+  end_byte: number;
+}
+
+export interface LinkAnchor {
+  kind: 'heading' | 'block';
+  name: string;
+  range: SourceRange;
+}
+
+export interface WikilinkTarget {
+  path: string;
+  anchor: LinkAnchor | null;
+}
+
+export interface LinkCandidate {
+  path: string;
+  distance: number;
+  alias: string | null;
+}
+
+/** Offsets are UTF-8 byte offsets, like `SourceRange`. */
+export interface SourceSegment {
+  start: number;
+  end: number;
+  path: string;
+  source_start: number;
+  depth: number;
+}
+
+export interface SkippedEmbed {
+  path: string;
+  line: number;
+  target: string;
+  reason: string;
+}
+
+export interface RenderedNote {
+  path: string;
+  content: string;
+  source_map: SourceSegment[];
+  skipped_embeds: SkippedEmbed[];
+  query_blocks: QueryBlock[];
+}
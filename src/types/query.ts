@@ -0,0 +1,24 @@
+export type QueryResultKind = 'table' | 'list';
+
+export interface QueryRow {
+  path: string;
+  values: unknown[];
+}
+
+/** Unless the query says `WITHOUT ID`, the first column is the file. */
+export interface QueryResult {
+  kind: QueryResultKind;
+  columns: string[];
+  rows: QueryRow[];
+}
+
+/** A ```query block in a rendered note; exactly one of result and error is set. */
+export interface QueryBlock {
+  path: string;
+  line: number;
+  start: number;
+  end: number;
+  source: string;
+  result: QueryResult | null;
+  error: string | null;
+}
@@ -2,6 +2,7 @@ export interface FileMatch {
   path: string;
   title: string | null;
   score: number;
+  matched_alias: string | null;
 }
 
 export interface ContentMatch {
@@ -11,3 +12,62 @@ export interface ContentMatch {
   matches: number;
 }
 
+
+export interface FileChangedEvent {
+  path: string;
+  kind: 'created' | 'modified' | 'removed';
+}
+
+export interface IndexJob {
+  root: string;
+  priority: number;
+  status: 'pending' | 'running';
+  enqueued_at: number;
+  started_at: number | null;
+  force: boolean;
+}
+
+export interface IndexedRoot {
+  path: string;
+  added_at: number;
+  last_indexed_at: number | null;
+  file_count: number;
+  follow_symlinks: boolean;
+  max_depth: number | null;
+}
+
+export interface IndexStats {
+  added: number;
+  updated: number;
+  removed: number;
+  unchanged: number;
+  skipped: SkippedEntry[];
+}
+
+export interface SkippedEntry {
+  path: string;
+  reason: string;
+}
+
+export interface IndexProgress {
+  root: string;
+  done: number;
+  total: number;
+  current_path: string;
+  eta_seconds: number;
+}
+
+export interface IndexCheckReport {
+  fts_ok: boolean;
+  fts_error: string | null;
+  file_rows: number;
+  fts_rows: number;
+  missing_files: string[];
+  outside_roots: string[];
+}
+
+export interface IndexRepairReport {
+  removed_missing: number;
+  fts_rebuilt: boolean;
+  after: IndexCheckReport;
+}
@@ -0,0 +1,32 @@
+export type TaskPriority = 'highest' | 'high' | 'medium' | 'low' | 'lowest';
+
+export interface Task {
+  path: string;
+  line: number;
+  status: string;
+  completed: boolean;
+  text: string;
+  heading: string | null;
+  due: string | null;
+  scheduled: string | null;
+  start: string | null;
+  created: string | null;
+  done: string | null;
+  priority: TaskPriority | null;
+  recurrence: string | null;
+  tags: string[];
+}
+
+export interface TaskFilter {
+  completed?: boolean;
+  folder?: string;
+  path?: string;
+  tag?: string;
+  recurring?: boolean;
+  /** `YYYY-MM-DD`, `today`, `tomorrow`, `yesterday`, `in 2 weeks` or `3 days ago` */
+  due_before?: string;
+  due_after?: string;
+  priority?: TaskPriority;
+  text?: string;
+  limit?: number;
+}